@@ -0,0 +1,440 @@
+use anyhow::{format_err, Result};
+use flate2::read::GzDecoder;
+use nodejs_semver::Version;
+use regex::Regex;
+use reqwest::header::{ACCEPT, USER_AGENT};
+use serde::Deserialize;
+use std::env;
+use std::fmt::Display;
+use std::io::Read;
+use std::sync::{Arc, LazyLock};
+use tar::Archive;
+
+use super::{
+  package_info::PackageInfo,
+  registry::RegistryClient,
+  versions::{current_version_of, DependencySpec},
+};
+
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Overrides the GitHub API, e.g. with a GitHub Enterprise or a local mock server.
+const GITHUB_API_URL_ENV: &str = "PUSHAPP_GITHUB_API_URL";
+
+const CHANGELOG_FILENAMES: [&str; 4] = ["changelog.md", "changes.md", "history.md", "releases.md"];
+
+/// Lines of release notes printed for each version.
+const MAX_NOTES_LINES: usize = 20;
+
+static VERSION_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?").unwrap());
+
+/// The notes of a single release.
+#[derive(Debug, PartialEq)]
+pub struct ReleaseNotes {
+  pub version: Version,
+  pub body: String,
+}
+
+/// Where the release notes of an update come from.
+#[derive(Debug, PartialEq)]
+pub enum ChangelogSource {
+  /// The releases of a GitHub repository: `owner/repo`.
+  GithubReleases(String),
+  /// The changelog file shipped in the package tarball.
+  Tarball,
+}
+
+impl Display for ChangelogSource {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ChangelogSource::GithubReleases(repository) => write!(f, "GitHub releases of {repository}"),
+      ChangelogSource::Tarball => write!(f, "changelog of the package"),
+    }
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+  tag_name: String,
+  body: Option<String>,
+  #[serde(default)]
+  draft: bool,
+}
+
+/// Fetch the release notes of updates, from GitHub releases or from the changelog in the tarball.
+#[derive(Debug)]
+pub struct ChangelogFetcher {
+  registry: Arc<RegistryClient>,
+  github_api: String,
+  github_token: Option<String>,
+}
+
+impl ChangelogFetcher {
+  pub fn new(registry: Arc<RegistryClient>) -> Self {
+    Self {
+      registry,
+      github_api: env::var(GITHUB_API_URL_ENV).unwrap_or_else(|_| GITHUB_API_URL.to_string()),
+      github_token: env::var("GITHUB_TOKEN").ok(),
+    }
+  }
+
+  /// Notes of the releases after the current version up to the target one, newest first.
+  pub async fn release_notes(
+    &self,
+    package: &PackageInfo,
+  ) -> Result<Option<(ChangelogSource, Vec<ReleaseNotes>)>> {
+    let (name, range) = match DependencySpec::parse(&package.current_version) {
+      DependencySpec::Alias { name, range } => (name, range),
+      DependencySpec::Git {
+        range: Some(range), ..
+      } => (package.pkg_name.as_str(), range),
+      _ => (package.pkg_name.as_str(), package.current_version.as_str()),
+    };
+    let current = current_version_of(range)?;
+    let target = Version::parse(&package.latest_version)?;
+
+    // GitHub errors, like the rate limit of anonymous calls, leave the tarball to fall back on
+    if let Some(repository) = package.repository.as_deref().and_then(github_repository) {
+      let notes = self
+        .github_release_notes(&repository, name, &current, &target)
+        .await
+        .unwrap_or_default();
+      if !notes.is_empty() {
+        return Ok(Some((ChangelogSource::GithubReleases(repository), notes)));
+      }
+    }
+
+    if let Some(tarball) = &package.tarball {
+      let tarball = self.registry.fetch_tarball(name, tarball).await?;
+      let notes = extract_changelog(&tarball)?
+        .map(|changelog| changelog_sections(&changelog, &current, &target))
+        .unwrap_or_default();
+      if !notes.is_empty() {
+        return Ok(Some((ChangelogSource::Tarball, notes)));
+      }
+    }
+
+    Ok(None)
+  }
+
+  async fn github_release_notes(
+    &self,
+    repository: &str,
+    name: &str,
+    current: &Version,
+    target: &Version,
+  ) -> Result<Vec<ReleaseNotes>> {
+    let url = format!(
+      "{}/repos/{repository}/releases?per_page=100",
+      self.github_api.trim_end_matches('/')
+    );
+
+    let mut request = self
+      .registry
+      .client
+      .get(url)
+      .header(ACCEPT, "application/vnd.github+json")
+      .header(USER_AGENT, env!("CARGO_PKG_NAME"));
+    if let Some(token) = &self.github_token {
+      request = request.bearer_auth(token);
+    }
+
+    let response = request.send().await?;
+    if !response.status().is_success() {
+      return Err(format_err!(
+        "GitHub answered {} for {repository}",
+        response.status()
+      ));
+    }
+
+    let releases: Vec<GithubRelease> = response.json().await?;
+    let mut notes: Vec<ReleaseNotes> = releases
+      .into_iter()
+      .filter(|release| !release.draft)
+      .filter_map(|release| {
+        let version = tag_version(&release.tag_name, name)?;
+        (*current < version && version <= *target).then(|| ReleaseNotes {
+          version,
+          body: release.body.unwrap_or_default().trim().to_string(),
+        })
+      })
+      .collect();
+    notes.sort_by(|a, b| b.version.cmp(&a.version));
+
+    Ok(notes)
+  }
+}
+
+/// Render release notes, truncating long ones.
+pub fn format_release_notes(notes: &[ReleaseNotes]) -> String {
+  notes
+    .iter()
+    .map(|release| {
+      let lines: Vec<&str> = release.body.lines().collect();
+      let mut text = format!("## {}", release.version);
+      for line in lines.iter().take(MAX_NOTES_LINES) {
+        text.push_str(&format!("\n{line}"));
+      }
+      if lines.len() > MAX_NOTES_LINES {
+        text.push_str(&format!("\n… {} more lines", lines.len() - MAX_NOTES_LINES));
+      }
+      text
+    })
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+/// `owner/repo` of a `repository` field pointing to GitHub.
+fn github_repository(repository: &str) -> Option<String> {
+  // Normalizes the `github:` prefix and the `owner/repo` shorthand
+  let url = match DependencySpec::parse(repository) {
+    DependencySpec::Git { url, .. } => url,
+    _ => repository.to_string(),
+  };
+
+  let (_, path) = url.split_once("github.com")?;
+  let mut segments = path
+    .trim_start_matches([':', '/'])
+    .split('/')
+    .filter(|segment| !segment.is_empty());
+  let owner = segments.next()?;
+  let repo = segments.next()?.trim_end_matches(".git");
+
+  Some(format!("{owner}/{repo}"))
+}
+
+/// Version of a release tag: `v1.2.0`, `1.2.0` or `name@1.2.0` in monorepos.
+fn tag_version(tag: &str, name: &str) -> Option<Version> {
+  let version = match tag.rfind('@') {
+    Some(index) if index > 0 => (&tag[..index] == name).then(|| &tag[index + 1..])?,
+    _ => tag,
+  };
+
+  Version::parse(version.trim_start_matches('v')).ok()
+}
+
+/// Content of the changelog file at the root of a package tarball.
+fn extract_changelog(tarball: &[u8]) -> Result<Option<String>> {
+  let mut archive = Archive::new(GzDecoder::new(tarball));
+
+  for entry in archive.entries()? {
+    let mut entry = entry?;
+    let path = entry.path()?;
+
+    // Files are nested in a single directory, usually `package/`
+    let is_changelog = path.components().count() == 2
+      && path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| CHANGELOG_FILENAMES.contains(&name.to_lowercase().as_str()));
+
+    if is_changelog {
+      let mut changelog = String::new();
+      entry.read_to_string(&mut changelog)?;
+      return Ok(Some(changelog));
+    }
+  }
+
+  Ok(None)
+}
+
+/// Sections of a markdown changelog whose heading holds a version after `current` up to `target`.
+fn changelog_sections(changelog: &str, current: &Version, target: &Version) -> Vec<ReleaseNotes> {
+  let mut sections = Vec::new();
+  let mut section: Option<(Version, Vec<&str>)> = None;
+
+  for line in changelog.lines() {
+    let heading_version = line
+      .starts_with('#')
+      .then(|| VERSION_REGEX.find(line))
+      .flatten()
+      .and_then(|version| Version::parse(version.as_str()).ok());
+
+    match (heading_version, &mut section) {
+      (Some(version), _) => {
+        sections.extend(section.take());
+        section = Some((version, Vec::new()));
+      }
+      (None, Some((_, body))) => body.push(line),
+      (None, None) => {}
+    }
+  }
+  sections.extend(section);
+
+  let mut notes: Vec<ReleaseNotes> = sections
+    .into_iter()
+    .filter(|(version, _)| current < version && version <= target)
+    .map(|(version, body)| ReleaseNotes {
+      version,
+      body: body.join("\n").trim().to_string(),
+    })
+    .collect();
+  notes.sort_by(|a, b| b.version.cmp(&a.version));
+
+  notes
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cli::registry::NpmConfig;
+  use flate2::{write::GzEncoder, Compression};
+  use std::collections::HashMap;
+
+  fn version(version: &str) -> Version {
+    Version::parse(version).unwrap()
+  }
+
+  #[test]
+  fn test_github_repository() {
+    let repositories = [
+      "git+https://github.com/facebook/react.git",
+      "https://github.com/facebook/react/tree/main/packages/react",
+      "git@github.com:facebook/react.git",
+      "github:facebook/react",
+      "facebook/react",
+    ];
+
+    for repository in repositories {
+      assert_eq!(
+        github_repository(repository).as_deref(),
+        Some("facebook/react")
+      );
+    }
+    assert_eq!(github_repository("https://gitlab.com/foo/bar.git"), None);
+  }
+
+  #[test]
+  fn test_tag_version() {
+    assert_eq!(tag_version("v1.2.0", "react"), Some(version("1.2.0")));
+    assert_eq!(tag_version("react@1.2.0", "react"), Some(version("1.2.0")));
+    assert_eq!(
+      tag_version("@babel/core@7.1.0", "@babel/core"),
+      Some(version("7.1.0"))
+    );
+    assert_eq!(tag_version("react-dom@1.2.0", "react"), None);
+    assert_eq!(tag_version("nightly", "react"), None);
+  }
+
+  const CHANGELOG: &str = "# Changelog\n\n## [1.2.0] - 2024-03-01\n\n### Features\n\n- Faster\n\n## 1.1.0\n\n- Smaller\n\n## v1.0.0\n\n- First release\n";
+
+  #[test]
+  fn test_changelog_sections() {
+    let notes = changelog_sections(CHANGELOG, &version("1.0.0"), &version("1.2.0"));
+
+    assert_eq!(
+      notes,
+      [
+        ReleaseNotes {
+          version: version("1.2.0"),
+          body: "### Features\n\n- Faster".to_string()
+        },
+        ReleaseNotes {
+          version: version("1.1.0"),
+          body: "- Smaller".to_string()
+        },
+      ]
+    );
+  }
+
+  fn tarball() -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, contents) in [
+      ("package/package.json", "{}"),
+      ("package/CHANGELOG.md", CHANGELOG),
+    ] {
+      let mut header = tar::Header::new_gnu();
+      header.set_size(contents.len() as u64);
+      header.set_cksum();
+      builder
+        .append_data(&mut header, path, contents.as_bytes())
+        .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+  }
+
+  #[test]
+  fn test_extract_changelog() {
+    assert_eq!(
+      extract_changelog(&tarball()).unwrap().as_deref(),
+      Some(CHANGELOG)
+    );
+  }
+
+  #[tokio::test]
+  async fn test_github_release_notes() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+      .mock("GET", "/repos/facebook/react/releases?per_page=100")
+      .with_body(
+        r#"[
+          { "tag_name": "v19.0.0", "body": "Actions" },
+          { "tag_name": "v18.3.0", "body": "Warnings" },
+          { "tag_name": "v18.2.0", "body": "Fixes" },
+          { "tag_name": "v20.0.0", "body": "Draft", "draft": true }
+        ]"#,
+      )
+      .create_async()
+      .await;
+
+    let fetcher = ChangelogFetcher {
+      registry: Arc::new(RegistryClient::new(NpmConfig::from_values(HashMap::new()))),
+      github_api: server.url(),
+      github_token: None,
+    };
+    let package = PackageInfo {
+      pkg_name: "react".to_string(),
+      current_version: "^18.2.0".to_string(),
+      latest_version: "20.0.0".to_string(),
+      repository: Some("git+https://github.com/facebook/react.git".to_string()),
+      ..Default::default()
+    };
+
+    let (source, notes) = fetcher.release_notes(&package).await.unwrap().unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(
+      source,
+      ChangelogSource::GithubReleases("facebook/react".to_string())
+    );
+    let versions: Vec<String> = notes.iter().map(|note| note.version.to_string()).collect();
+    assert_eq!(versions, ["19.0.0", "18.3.0"]);
+  }
+
+  #[tokio::test]
+  async fn test_release_notes_fall_back_on_tarball() {
+    let mut server = mockito::Server::new_async().await;
+    let github = server
+      .mock("GET", "/repos/acme/widget/releases?per_page=100")
+      .with_status(403)
+      .create_async()
+      .await;
+    let tarball_mock = server
+      .mock("GET", "/widget/-/widget-1.2.0.tgz")
+      .with_body(tarball())
+      .create_async()
+      .await;
+
+    let fetcher = ChangelogFetcher {
+      registry: Arc::new(RegistryClient::new(NpmConfig::from_values(HashMap::new()))),
+      github_api: server.url(),
+      github_token: None,
+    };
+    let package = PackageInfo {
+      pkg_name: "widget".to_string(),
+      current_version: "^1.0.0".to_string(),
+      latest_version: "1.2.0".to_string(),
+      repository: Some("github:acme/widget".to_string()),
+      tarball: Some(format!("{}/widget/-/widget-1.2.0.tgz", server.url())),
+      ..Default::default()
+    };
+
+    let (source, notes) = fetcher.release_notes(&package).await.unwrap().unwrap();
+
+    github.assert_async().await;
+    tarball_mock.assert_async().await;
+    assert_eq!(source, ChangelogSource::Tarball);
+    assert_eq!(notes.len(), 2);
+  }
+}
@@ -0,0 +1,192 @@
+use anyhow::{format_err, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{
+  filters::PackageFilter,
+  versions::{MinAge, VersionTarget},
+};
+use crate::utils::{
+  fs::{read_json, read_toml},
+  hashmap::merge,
+};
+
+pub static RC_FILENAME: &str = ".pushapprc";
+pub static TOML_FILENAME: &str = "pushapp.toml";
+
+/// Defaults for the command line options, read from `.pushapprc`, `pushapp.toml` or the
+/// `"pushapp"` key of `package.json`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct Config {
+  pub target: Option<VersionTarget>,
+  pub filter: Option<Vec<PackageFilter>>,
+  pub reject: Option<Vec<PackageFilter>>,
+  /// Per-package targets: `{ "react": "minor", "@babel/*": "patch" }`.
+  pub targets: HashMap<String, VersionTarget>,
+  /// Per-package ignored versions: `{ "react": ">=19", "left-pad": "*" }`.
+  pub ignore: HashMap<String, String>,
+  pub min_age: Option<MinAge>,
+  pub peer: Option<bool>,
+  pub overrides: Option<bool>,
+  pub audit: Option<bool>,
+  pub time: Option<bool>,
+  pub changelog: Option<bool>,
+  pub prefer_offline: Option<bool>,
+  pub concurrency: Option<u16>,
+  pub registry: Option<String>,
+  /// Per-scope registries: `{ "@company": "https://npm.company.com/" }`.
+  pub registries: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct ManifestConfig {
+  pushapp: Option<Config>,
+}
+
+impl Config {
+  /// Load the user config from the home directory, then the project config found next to
+  /// `package_json`, the latter taking precedence.
+  pub fn load(package_json: Option<&Path>) -> Result<Self> {
+    let user_config = match dirs::home_dir() {
+      Some(home_dir) => Self::read_dir(&home_dir)?,
+      None => None,
+    };
+    let project_config = match package_json {
+      Some(package_json) => Self::read_project(package_json)?,
+      None => None,
+    };
+
+    Ok(
+      [user_config, project_config]
+        .into_iter()
+        .flatten()
+        .fold(Self::default(), Self::merge),
+    )
+  }
+
+  /// Config files of the project directory, falling back to the `"pushapp"` key of `package.json`.
+  fn read_project(package_json: &Path) -> Result<Option<Self>> {
+    if let Some(config) = package_json
+      .parent()
+      .map(Self::read_dir)
+      .transpose()?
+      .flatten()
+    {
+      return Ok(Some(config));
+    }
+
+    let manifest: ManifestConfig = read_json(package_json)
+      .map_err(|e| format_err!("Invalid pushapp config in {}: {e}", package_json.display()))?;
+    Ok(manifest.pushapp)
+  }
+
+  fn read_dir(dir: &Path) -> Result<Option<Self>> {
+    let rc_file = dir.join(RC_FILENAME);
+    let toml_file = dir.join(TOML_FILENAME);
+
+    let config = if rc_file.is_file() {
+      read_json(&rc_file).map_err(|e| format_err!("Invalid {}: {e}", rc_file.display()))?
+    } else if toml_file.is_file() {
+      read_toml(&toml_file).map_err(|e| format_err!("Invalid {}: {e}", toml_file.display()))?
+    } else {
+      return Ok(None);
+    };
+
+    Ok(Some(config))
+  }
+
+  /// Layer `other` on top of `self`.
+  fn merge(self, other: Self) -> Self {
+    Self {
+      target: other.target.or(self.target),
+      filter: other.filter.or(self.filter),
+      reject: other.reject.or(self.reject),
+      targets: merge(&[Some(&self.targets), Some(&other.targets)]),
+      ignore: merge(&[Some(&self.ignore), Some(&other.ignore)]),
+      min_age: other.min_age.or(self.min_age),
+      peer: other.peer.or(self.peer),
+      overrides: other.overrides.or(self.overrides),
+      audit: other.audit.or(self.audit),
+      time: other.time.or(self.time),
+      changelog: other.changelog.or(self.changelog),
+      prefer_offline: other.prefer_offline.or(self.prefer_offline),
+      concurrency: other.concurrency.or(self.concurrency),
+      registry: other.registry.or(self.registry),
+      registries: merge(&[Some(&self.registries), Some(&other.registries)]),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cli::package_json::PACKAGE_JSON_FILENAME;
+  use std::fs;
+  use tempfile::tempdir;
+
+  #[test]
+  fn test_read_project_from_package_json() {
+    let dir = tempdir().unwrap();
+    let package_json = dir.path().join(PACKAGE_JSON_FILENAME);
+    fs::write(
+      &package_json,
+      r#"{ "name": "app", "pushapp": { "target": "minor", "reject": ["typescript"], "targets": { "react": "patch" } } }"#,
+    )
+    .unwrap();
+
+    let config = Config::read_project(&package_json).unwrap().unwrap();
+
+    assert!(matches!(config.target, Some(VersionTarget::Minor)));
+    assert_eq!(config.reject.unwrap().len(), 1);
+    assert!(matches!(config.targets["react"], VersionTarget::Patch));
+  }
+
+  #[test]
+  fn test_config_files_take_precedence_over_package_json() {
+    let dir = tempdir().unwrap();
+    let package_json = dir.path().join(PACKAGE_JSON_FILENAME);
+    fs::write(&package_json, r#"{ "pushapp": { "target": "minor" } }"#).unwrap();
+    fs::write(
+      dir.path().join(TOML_FILENAME),
+      "target = \"patch\"\nfilter = [\"@babel/*\"]\n\n[registries]\n\"@company\" = \"https://npm.company.com/\"\n",
+    )
+    .unwrap();
+
+    let config = Config::read_project(&package_json).unwrap().unwrap();
+
+    assert!(matches!(config.target, Some(VersionTarget::Patch)));
+    assert_eq!(config.filter.unwrap().len(), 1);
+    assert_eq!(config.registries["@company"], "https://npm.company.com/");
+  }
+
+  #[test]
+  fn test_merge() {
+    let user_config = Config {
+      target: Some(VersionTarget::Minor),
+      registry: Some("https://npm.company.com/".to_string()),
+      targets: HashMap::from([("react".to_string(), VersionTarget::Patch)]),
+      ..Default::default()
+    };
+    let project_config = Config {
+      target: Some(VersionTarget::Semver),
+      targets: HashMap::from([("react".to_string(), VersionTarget::Major)]),
+      ..Default::default()
+    };
+
+    let config = user_config.merge(project_config);
+
+    assert!(matches!(config.target, Some(VersionTarget::Semver)));
+    assert!(matches!(config.targets["react"], VersionTarget::Major));
+    assert_eq!(config.registry.unwrap(), "https://npm.company.com/");
+  }
+
+  #[test]
+  fn test_invalid_config() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join(RC_FILENAME), r#"{ "target": "newest" }"#).unwrap();
+
+    assert!(Config::read_dir(dir.path()).is_err());
+  }
+}
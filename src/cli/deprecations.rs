@@ -0,0 +1,137 @@
+use colored::Colorize;
+use regex::Regex;
+use std::fmt::Display;
+use std::sync::LazyLock;
+
+/// Phrases of a deprecation message introducing the package to use instead: `use X instead`,
+/// `renamed to @scope/x`, `is now X`.
+static REPLACEMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(
+    r#"(?i:\b(?:use|using|replaced by|moved to|renamed to|switch to|migrate to|in favou?r of|is now))\s+(?i:the\s+)?[`'"]?((?:@[a-z0-9][a-z0-9._-]*/)?[a-z0-9][a-z0-9._-]*)"#,
+  )
+  .unwrap()
+});
+
+/// Words following the phrases that aren't package names.
+const NOT_PACKAGES: [&str; 15] = [
+  "a", "an", "the", "it", "this", "that", "these", "your", "our", "version", "versions", "v",
+  "npm", "native", "instead",
+];
+
+/// A deprecated version, currently used or proposed as the update.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deprecation {
+  pub name: String,
+  pub version: String,
+  pub workspace: Option<String>,
+  pub message: String,
+  /// Whether the deprecated version is the proposed update rather than the current one.
+  pub is_target: bool,
+}
+
+impl Deprecation {
+  /// Package the message suggests instead, when it names one other than the deprecated package.
+  pub fn replacement(&self) -> Option<&str> {
+    REPLACEMENT_REGEX
+      .captures_iter(&self.message)
+      .filter_map(|captures| captures.get(1))
+      .map(|name| name.as_str().trim_end_matches(['.', '-', '_']))
+      .find(|name| {
+        !name.is_empty()
+          && !NOT_PACKAGES.contains(name)
+          && !name.starts_with(|c: char| c.is_ascii_digit())
+          && *name != self.name
+      })
+  }
+}
+
+impl Display for Deprecation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if let Some(workspace) = &self.workspace {
+      write!(f, "{} ", format!("[{workspace}]").dimmed())?;
+    }
+
+    write!(f, "{}@{}", self.name, self.version)?;
+    if self.is_target {
+      write!(f, " {}", "(proposed update)".dimmed())?;
+    }
+    write!(f, ": {}", self.message)?;
+
+    if let Some(replacement) = self.replacement() {
+      write!(f, " {}", format!("→ consider {replacement}").bright_blue())?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn replacement_of(name: &str, message: &str) -> Option<String> {
+    Deprecation {
+      name: name.to_string(),
+      version: "1.0.0".to_string(),
+      workspace: None,
+      message: message.to_string(),
+      is_target: false,
+    }
+    .replacement()
+    .map(str::to_string)
+  }
+
+  #[test]
+  fn test_replacement() {
+    assert_eq!(
+      replacement_of(
+        "babel-eslint",
+        "babel-eslint is now @babel/eslint-parser. This package will no longer receive updates."
+      )
+      .as_deref(),
+      Some("@babel/eslint-parser")
+    );
+    assert_eq!(
+      replacement_of(
+        "node-sass",
+        "Node Sass is no longer supported. Please use `sass` instead."
+      )
+      .as_deref(),
+      Some("sass")
+    );
+    assert_eq!(
+      replacement_of("left-pad", "This package has been renamed to pad-left.").as_deref(),
+      Some("pad-left")
+    );
+  }
+
+  #[test]
+  fn test_no_replacement() {
+    assert_eq!(
+      replacement_of(
+        "request",
+        "request has been deprecated, see https://github.com/request/request/issues/3142"
+      ),
+      None
+    );
+    assert_eq!(
+      replacement_of(
+        "uuid",
+        "Please upgrade  to version 7 or higher.  Older versions may use Math.random() in certain circumstances."
+      ),
+      None
+    );
+    assert_eq!(
+      replacement_of(
+        "querystring",
+        "The querystring API is considered Legacy. new code should use the URLSearchParams API instead."
+      ),
+      None
+    );
+    assert_eq!(replacement_of("glob", "Please use glob v9 or later"), None);
+    assert_eq!(
+      replacement_of("abab", "Use your platform's native atob() instead"),
+      None
+    );
+  }
+}
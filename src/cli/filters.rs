@@ -0,0 +1,203 @@
+use anyhow::{format_err, Error, Result};
+use clap::ValueEnum;
+use glob::Pattern;
+use nodejs_semver::Range;
+use regex::Regex;
+use serde::Deserialize;
+use std::str::FromStr;
+
+use super::{package_json::Dependency, versions::VersionTarget};
+
+/// A package name matcher, used by `--filter` and `--reject`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub enum PackageFilter {
+  /// A package name or a glob: `react`, `@babel/*`.
+  Glob(Pattern),
+  /// A regular expression between slashes: `/^eslint-/`.
+  Regex(Regex),
+}
+
+impl PackageFilter {
+  pub fn matches(&self, name: &str) -> bool {
+    match self {
+      PackageFilter::Glob(pattern) => pattern.matches(name),
+      PackageFilter::Regex(regex) => regex.is_match(name),
+    }
+  }
+}
+
+impl FromStr for PackageFilter {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self> {
+    match s.strip_prefix('/').and_then(|s| s.strip_suffix('/')) {
+      Some(regex) if !regex.is_empty() => Ok(PackageFilter::Regex(Regex::new(regex)?)),
+      _ => Ok(PackageFilter::Glob(Pattern::new(s)?)),
+    }
+  }
+}
+
+impl TryFrom<String> for PackageFilter {
+  type Error = Error;
+
+  fn try_from(value: String) -> Result<Self> {
+    value.parse()
+  }
+}
+
+impl PackageFilter {
+  /// Whether the filter is a plain package name, checked before globs and regexes.
+  pub fn is_name(&self) -> bool {
+    match self {
+      PackageFilter::Glob(pattern) => Pattern::escape(pattern.as_str()) == pattern.as_str(),
+      PackageFilter::Regex(_) => false,
+    }
+  }
+}
+
+/// The version target of the packages matching `filter`: `react=minor`, `@babel/*=patch`.
+#[derive(Debug, Clone)]
+pub struct PackageTarget {
+  pub filter: PackageFilter,
+  pub target: VersionTarget,
+}
+
+impl FromStr for PackageTarget {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self> {
+    let (filter, target) = s
+      .rsplit_once('=')
+      .ok_or_else(|| format_err!("Expected <package>=<target>, got \"{s}\"."))?;
+
+    Ok(Self {
+      filter: filter.parse()?,
+      target: VersionTarget::from_str(target, true).map_err(|e| format_err!(e))?,
+    })
+  }
+}
+
+/// Versions to skip for the packages matching `filter`: `react@>=19`, or every version with `react`.
+#[derive(Debug, Clone)]
+pub struct PackageIgnore {
+  pub filter: PackageFilter,
+  pub range: Range,
+}
+
+impl FromStr for PackageIgnore {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self> {
+    // Scoped names start with `@`, the range follows the last one
+    let (filter, range) = match s.rfind('@') {
+      Some(index) if index > 0 => (&s[..index], &s[index + 1..]),
+      _ => (s, "*"),
+    };
+
+    Ok(Self {
+      filter: filter.parse()?,
+      range: Range::parse(range)?,
+    })
+  }
+}
+
+/// Keep the dependencies matching any of `filters` (all of them when empty) and none of `rejects`.
+pub fn filter_dependencies(
+  deps: Vec<Dependency>,
+  filters: &[PackageFilter],
+  rejects: &[PackageFilter],
+) -> (Vec<Dependency>, Vec<Dependency>) {
+  deps.into_iter().partition(|dep| {
+    (filters.is_empty() || filters.iter().any(|filter| filter.matches(&dep.name)))
+      && !rejects.iter().any(|reject| reject.matches(&dep.name))
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn dependencies(names: &[&str]) -> Vec<Dependency> {
+    names
+      .iter()
+      .map(|name| Dependency {
+        name: (*name).to_string(),
+        version: "^1.0.0".to_string(),
+        dep_type: None,
+        workspace: None,
+        manager: None,
+      })
+      .collect()
+  }
+
+  fn names(deps: &[Dependency]) -> Vec<&str> {
+    deps.iter().map(|dep| dep.name.as_str()).collect()
+  }
+
+  #[test]
+  fn test_parse_filter() {
+    assert!(matches!("react".parse(), Ok(PackageFilter::Glob(_))));
+    assert!(matches!("@babel/*".parse(), Ok(PackageFilter::Glob(_))));
+    assert!(matches!("/^eslint-/".parse(), Ok(PackageFilter::Regex(_))));
+    assert!("/[/".parse::<PackageFilter>().is_err());
+  }
+
+  #[test]
+  fn test_filter_dependencies() {
+    let deps = dependencies(&[
+      "@babel/core",
+      "@babel/preset-env",
+      "eslint-plugin-react",
+      "react",
+      "typescript",
+    ]);
+    let filters = [
+      "@babel/*".parse().unwrap(),
+      "/^eslint-/".parse().unwrap(),
+      "react".parse().unwrap(),
+    ];
+    let rejects = ["@babel/preset-*".parse().unwrap()];
+
+    let (filtered, left_out) = filter_dependencies(deps, &filters, &rejects);
+
+    assert_eq!(
+      names(&filtered),
+      ["@babel/core", "eslint-plugin-react", "react"]
+    );
+    assert_eq!(names(&left_out), ["@babel/preset-env", "typescript"]);
+  }
+
+  #[test]
+  fn test_reject_only() {
+    let deps = dependencies(&["react", "typescript"]);
+
+    let (filtered, _) = filter_dependencies(deps, &[], &["typescript".parse().unwrap()]);
+
+    assert_eq!(names(&filtered), ["react"]);
+  }
+
+  #[test]
+  fn test_parse_package_rules() {
+    let target: PackageTarget = "@babel/*=patch".parse().unwrap();
+    assert!(target.filter.matches("@babel/core"));
+    assert!(!target.filter.is_name());
+    assert!(matches!(target.target, VersionTarget::Patch));
+    assert!("react".parse::<PackageTarget>().is_err());
+    assert!("react=newest".parse::<PackageTarget>().is_err());
+
+    let ignore: PackageIgnore = "@types/node@>=21".parse().unwrap();
+    assert!(ignore.filter.is_name());
+    assert!(ignore.filter.matches("@types/node"));
+    assert_eq!(
+      ignore.range.to_string(),
+      Range::parse(">=21").unwrap().to_string()
+    );
+
+    let pinned: PackageIgnore = "react".parse().unwrap();
+    assert_eq!(
+      pinned.range.to_string(),
+      Range::parse("*").unwrap().to_string()
+    );
+  }
+}
@@ -1,6 +1,20 @@
-use clap::Parser;
+use anyhow::{Error, Result};
+use chrono::Utc;
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum};
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
 
-use super::versions::VersionTarget;
+use super::{
+  config::Config,
+  filters::{PackageFilter, PackageIgnore, PackageTarget},
+  package_json::PACKAGE_JSON_FILENAME,
+  package_manager::PackageManager,
+  registry::CacheMode,
+  report::OutputFormat,
+  versions::{MinAge, UpdateRule, VersionTarget},
+};
+use crate::utils::fs::find_closest_file;
 
 #[derive(Parser, Debug, Default)]
 #[command(author, version, about, long_about = None)]
@@ -15,7 +29,345 @@ pub struct Flags {
   /// Check global packages instead of in the current project.
   #[clap(short, long)]
   pub global: bool,
-  /// Determines the version to upgrade to.
-  #[clap(short, long, verbatim_doc_comment, default_value = "latest")]
+  /// Apply every available update without prompting.
+  #[clap(short, long, visible_alias = "upgrade", conflicts_with = "check")]
+  pub yes: bool,
+  /// List available updates without installing them and exit with a non-zero status if any is found.
+  #[clap(long)]
+  pub check: bool,
+  /// Only update the version ranges in package.json, without running the package manager.
+  #[clap(long)]
+  pub no_install: bool,
+  /// Print the package manager command and the package.json changes of the selected updates without applying them.
+  #[clap(long)]
+  pub dry_run: bool,
+  /// Package manager installing the updates, instead of the one detected from package.json or the lock file.
+  /// With --global, only check the global packages of this package manager.
+  #[clap(long, value_name = "NAME")]
+  pub package_manager: Option<PackageManager>,
+  /// Print the available updates in the given format instead of prompting.
+  #[clap(short, long)]
+  pub format: Option<OutputFormat>,
+  /// Only check packages matching a name, a glob or a /regex/. Can be repeated or comma-separated.
+  #[clap(long, value_delimiter = ',')]
+  pub filter: Vec<PackageFilter>,
+  /// Skip packages matching a name, a glob or a /regex/. Can be repeated or comma-separated.
+  #[clap(long, value_delimiter = ',')]
+  pub reject: Vec<PackageFilter>,
+  /// Determines the version to upgrade to: latest (default), semver, major, minor, patch, pre, next, canary, rc, beta or alpha.
+  /// Use <package>=<target> to set the target of matching packages only, e.g. react=minor or "@babel/*=patch".
+  #[clap(short, long = "target", value_name = "TARGET", verbatim_doc_comment)]
+  pub target_args: Vec<TargetArg>,
+  /// Skip the versions of a package in a range, e.g. "react@>=19", or all of them with just the name.
+  #[clap(long, value_name = "PACKAGE[@RANGE]")]
+  pub ignore: Vec<PackageIgnore>,
+  /// Also check the "peerDependencies" ranges, widening them to accept the new versions.
+  #[clap(long)]
+  pub peer: bool,
+  /// Also check the version pins of "overrides", "resolutions" and "pnpm.overrides".
+  #[clap(long)]
+  pub overrides: bool,
+  /// Look the current versions up in the registry security advisories and flag the vulnerable ones.
+  #[clap(long)]
+  pub audit: bool,
+  /// Only update vulnerable packages, to the lowest version fixing their advisories. Implies --audit.
+  #[clap(long)]
+  pub security_only: bool,
+  /// Show when the current and target versions were published, fetching the full package metadata.
+  #[clap(long)]
+  pub time: bool,
+  /// Show the release notes between the current and target versions of each update.
+  #[clap(long)]
+  pub changelog: bool,
+  /// Only propose versions published at least this long ago, e.g. 7d, 12h or 2w.
+  #[clap(long, value_name = "AGE")]
+  pub min_age: Option<MinAge>,
+  /// Version target of the packages without a specific one.
+  #[clap(skip)]
   pub target: VersionTarget,
+  /// Maximum number of registry requests at once, instead of the "maxsockets" of .npmrc (12 by default).
+  #[clap(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+  pub concurrency: Option<u16>,
+  /// Answer from the package metadata cache only, without any network request.
+  #[clap(long, conflicts_with = "prefer_offline")]
+  pub offline: bool,
+  /// Use the cached package metadata without revalidating it, only fetching the missing packages.
+  #[clap(long)]
+  pub prefer_offline: bool,
+  /// Registry to look packages up in, instead of the one configured in .npmrc.
+  #[clap(long)]
+  pub registry: Option<String>,
+  /// Per-package targets, the first matching one applies.
+  #[clap(skip)]
+  pub package_targets: Vec<PackageTarget>,
+  /// Per-scope registries, from the config file.
+  #[clap(skip)]
+  pub registries: HashMap<String, String>,
+}
+
+/// A `--target` value, for every package or for the matching ones.
+#[derive(Debug, Clone)]
+pub enum TargetArg {
+  All(VersionTarget),
+  Package(PackageTarget),
+}
+
+impl FromStr for TargetArg {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self> {
+    if s.contains('=') {
+      return s.parse().map(TargetArg::Package);
+    }
+
+    VersionTarget::from_str(s, true)
+      .map(TargetArg::All)
+      .map_err(Error::msg)
+  }
+}
+
+impl Flags {
+  /// Parse the command line, filling the options it leaves unset from the config files.
+  pub fn load() -> Result<Self> {
+    let matches = Self::command().get_matches();
+    let mut flags = Self::from_arg_matches(&matches)?;
+
+    let package_json = if flags.global {
+      None
+    } else {
+      env::current_dir()
+        .ok()
+        .and_then(|cwd| find_closest_file(PACKAGE_JSON_FILENAME, cwd).ok())
+    };
+    flags.apply_config(Config::load(package_json.as_deref())?, &matches)?;
+
+    Ok(flags)
+  }
+
+  pub fn cache_mode(&self) -> CacheMode {
+    if self.offline {
+      CacheMode::Offline
+    } else if self.prefer_offline {
+      CacheMode::PreferOffline
+    } else {
+      CacheMode::Revalidate
+    }
+  }
+
+  /// How to update a package, honoring the per-package targets and ignored versions.
+  pub fn rule_for(&self, name: &str) -> UpdateRule {
+    let target = self
+      .package_targets
+      .iter()
+      .find(|package_target| package_target.filter.matches(name))
+      .map_or(&self.target, |package_target| &package_target.target);
+    let ignore = self
+      .ignore
+      .iter()
+      .find(|ignore| ignore.filter.matches(name))
+      .map(|ignore| ignore.range.clone());
+
+    UpdateRule {
+      target: target.clone(),
+      ignore,
+      published_before: self
+        .min_age
+        .map(|min_age| min_age.published_before(Utc::now())),
+      security_fix: None,
+    }
+  }
+
+  fn apply_config(&mut self, config: Config, matches: &ArgMatches) -> Result<()> {
+    let from_command_line = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+    let mut target = None;
+    for target_arg in &self.target_args {
+      match target_arg {
+        TargetArg::All(all) => target = Some(all.clone()),
+        TargetArg::Package(package_target) => self.package_targets.push(package_target.clone()),
+      }
+    }
+
+    // An explicit `--target` applies to every package the command line doesn't target
+    if target.is_none() {
+      self
+        .package_targets
+        .extend(sorted_rules(config.targets, |filter, target| {
+          Ok(PackageTarget { filter, target })
+        })?);
+    }
+    self.target = target.or(config.target).unwrap_or_default();
+    self
+      .ignore
+      .extend(sorted_rules(config.ignore, |filter, range: String| {
+        Ok(PackageIgnore {
+          filter,
+          range: range.parse()?,
+        })
+      })?);
+
+    if !from_command_line("filter") {
+      self.filter = config.filter.unwrap_or_default();
+    }
+    if !from_command_line("reject") {
+      self.reject = config.reject.unwrap_or_default();
+    }
+    if !from_command_line("peer") {
+      self.peer = config.peer.unwrap_or_default();
+    }
+    if !from_command_line("overrides") {
+      self.overrides = config.overrides.unwrap_or_default();
+    }
+    if !from_command_line("audit") {
+      self.audit = config.audit.unwrap_or_default();
+    }
+    if !from_command_line("concurrency") {
+      self.concurrency = config.concurrency;
+    }
+    if !from_command_line("prefer_offline") {
+      self.prefer_offline = config.prefer_offline.unwrap_or_default();
+    }
+    if !from_command_line("time") {
+      self.time = config.time.unwrap_or_default();
+    }
+    if !from_command_line("changelog") {
+      self.changelog = config.changelog.unwrap_or_default();
+    }
+    if !from_command_line("min_age") {
+      self.min_age = config.min_age;
+    }
+    if !from_command_line("registry") {
+      self.registry = config.registry;
+    }
+    self.registries = config.registries;
+
+    Ok(())
+  }
+}
+
+/// Build the rules of a config map, package names first so that they win over globs.
+fn sorted_rules<V, R, F>(rules: HashMap<String, V>, build: F) -> Result<Vec<R>>
+where
+  F: Fn(PackageFilter, V) -> Result<R>,
+{
+  let mut rules = rules
+    .into_iter()
+    .map(|(pattern, value)| Ok((pattern.parse::<PackageFilter>()?, pattern, value)))
+    .collect::<Result<Vec<_>>>()?;
+  rules.sort_by(|(a, a_pattern, _), (b, b_pattern, _)| {
+    (!a.is_name(), a_pattern).cmp(&(!b.is_name(), b_pattern))
+  });
+
+  rules
+    .into_iter()
+    .map(|(filter, _, value)| build(filter, value))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn flags_with_config(args: &[&str], config: Config) -> Flags {
+    let matches = Flags::command().get_matches_from(args);
+    let mut flags = Flags::from_arg_matches(&matches).unwrap();
+    flags.apply_config(config, &matches).unwrap();
+    flags
+  }
+
+  fn config() -> Config {
+    Config {
+      target: Some(VersionTarget::Minor),
+      reject: Some(vec!["typescript".parse().unwrap()]),
+      targets: HashMap::from([
+        ("react".to_string(), VersionTarget::Patch),
+        ("react*".to_string(), VersionTarget::Major),
+      ]),
+      ignore: HashMap::from([("typescript".to_string(), "<5.5.0".to_string())]),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_config_fills_unset_flags() {
+    let flags = flags_with_config(&["pushapp"], config());
+
+    assert!(matches!(flags.target, VersionTarget::Minor));
+    assert!(matches!(
+      flags.rule_for("react").target,
+      VersionTarget::Patch
+    ));
+    assert!(matches!(
+      flags.rule_for("react-dom").target,
+      VersionTarget::Major
+    ));
+    assert!(matches!(flags.rule_for("vue").target, VersionTarget::Minor));
+    assert!(flags.rule_for("typescript").ignore.is_some());
+    assert_eq!(flags.reject.len(), 1);
+  }
+
+  #[test]
+  fn test_command_line_takes_precedence() {
+    let flags = flags_with_config(
+      &["pushapp", "--target", "major", "--reject", "a,b"],
+      config(),
+    );
+
+    assert!(matches!(flags.target, VersionTarget::Major));
+    assert!(matches!(
+      flags.rule_for("react").target,
+      VersionTarget::Major
+    ));
+    assert_eq!(flags.reject.len(), 2);
+  }
+
+  #[test]
+  fn test_large_min_age() {
+    let flags = flags_with_config(&["pushapp", "--min-age", "100000000d"], Config::default());
+
+    assert!(flags.rule_for("react").published_before.is_some());
+  }
+
+  #[test]
+  fn test_package_targets_and_ignores() {
+    let flags = flags_with_config(
+      &[
+        "pushapp",
+        "-t",
+        "react=minor",
+        "-t",
+        "eslint*=patch",
+        "-t",
+        "next=canary",
+        "--ignore",
+        "@types/node@>=21",
+        "--ignore",
+        "left-pad",
+      ],
+      config(),
+    );
+
+    // Command line package targets win over the config ones
+    assert!(matches!(flags.target, VersionTarget::Minor));
+    assert!(matches!(
+      flags.rule_for("react").target,
+      VersionTarget::Minor
+    ));
+    assert!(matches!(
+      flags.rule_for("eslint-plugin-react").target,
+      VersionTarget::Patch
+    ));
+    assert!(matches!(
+      flags.rule_for("next").target,
+      VersionTarget::Canary
+    ));
+    assert!(matches!(
+      flags.rule_for("react-dom").target,
+      VersionTarget::Major
+    ));
+    assert!(flags.rule_for("@types/node").ignore.is_some());
+    assert!(flags.rule_for("left-pad").ignore.is_some());
+    assert!(flags.rule_for("typescript").ignore.is_some());
+    assert!(flags.rule_for("react").ignore.is_none());
+  }
 }
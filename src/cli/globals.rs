@@ -0,0 +1,185 @@
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use super::{
+  package_json::{Dependency, PackageJson, PACKAGE_JSON_FILENAME},
+  package_manager::PackageManager,
+};
+use crate::utils::fs::read_json;
+
+/// Package managers whose global packages are checked, in listing order.
+const GLOBAL_MANAGERS: [PackageManager; 4] = [
+  PackageManager::Npm,
+  PackageManager::Pnpm,
+  PackageManager::Yarn,
+  PackageManager::Bun,
+];
+
+#[derive(Deserialize, Debug)]
+pub struct GlobalDependency {
+  pub version: String,
+}
+
+/// Output of `npm ls --json`, and of every project listed by `pnpm ls --json`.
+#[derive(Deserialize, Debug, Default)]
+pub struct GlobalDependencies {
+  #[serde(default)]
+  pub dependencies: HashMap<String, GlobalDependency>,
+}
+
+/// Global packages of the chosen package manager, or of every installed one.
+///
+/// Each dependency records the package manager owning it, which installs its update.
+pub fn get_global_deps(chosen: Option<PackageManager>) -> Result<Vec<Dependency>> {
+  if let Some(manager) = chosen {
+    return list_globals(manager);
+  }
+
+  let mut dependencies = Vec::new();
+  for manager in GLOBAL_MANAGERS {
+    if manager.installed_version().is_none() {
+      continue;
+    }
+
+    // A manager without any global setup shouldn't prevent checking the others
+    match list_globals(manager) {
+      Ok(globals) => dependencies.extend(globals),
+      Err(e) => eprintln!(
+        "{}",
+        format!("⚠️  Couldn't list the global packages of {manager}: {e:#}").bright_yellow()
+      ),
+    }
+  }
+
+  Ok(dependencies)
+}
+
+fn list_globals(manager: PackageManager) -> Result<Vec<Dependency>> {
+  let versions = match manager {
+    PackageManager::Npm => {
+      let output = run(manager, &["ls", "--json", "--global", "--depth=0"])?;
+      serde_json::from_str::<GlobalDependencies>(&output)?.dependencies
+    }
+    PackageManager::Pnpm => {
+      let output = run(manager, &["ls", "--json", "--global", "--depth=0"])?;
+      serde_json::from_str::<Vec<GlobalDependencies>>(&output)?
+        .into_iter()
+        .flat_map(|project| project.dependencies)
+        .collect()
+    }
+    // Yarn classic has no JSON listing, but keeps the global packages in a regular project
+    PackageManager::Yarn => {
+      installed_versions(Path::new(run(manager, &["global", "dir"])?.trim()))?
+    }
+    PackageManager::Bun => parse_bun_list(&run(manager, &["pm", "ls", "--global"])?),
+    PackageManager::Deno => bail!("deno doesn't install global npm packages"),
+  };
+
+  Ok(
+    versions
+      .into_iter()
+      .map(|(name, package)| Dependency {
+        name,
+        version: package.version,
+        dep_type: None,
+        workspace: None,
+        manager: Some(manager),
+      })
+      .collect(),
+  )
+}
+
+fn run(manager: PackageManager, args: &[&str]) -> Result<String> {
+  let output = Command::new(manager.to_str())
+    .args(args)
+    .output()
+    .with_context(|| format!("Couldn't run {manager}"))?;
+
+  // npm exits with an error on extraneous or missing packages but still lists them
+  if !output.status.success() && output.stdout.is_empty() {
+    bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+  }
+
+  Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Versions of the dependencies of a project, read from its `node_modules`.
+fn installed_versions(dir: &Path) -> Result<HashMap<String, GlobalDependency>> {
+  let json: PackageJson = read_json(dir.join(PACKAGE_JSON_FILENAME))?;
+
+  Ok(
+    json
+      .dependencies
+      .unwrap_or_default()
+      .into_keys()
+      .filter_map(|name| {
+        let manifest = dir
+          .join("node_modules")
+          .join(&name)
+          .join(PACKAGE_JSON_FILENAME);
+        read_json(manifest).ok().map(|package| (name, package))
+      })
+      .collect(),
+  )
+}
+
+/// Parse the tree printed by `bun pm ls`: `├── typescript@5.4.5`.
+fn parse_bun_list(output: &str) -> HashMap<String, GlobalDependency> {
+  output
+    .lines()
+    .filter_map(|line| line.split_once("── "))
+    .filter_map(|(_, package)| package.trim().rsplit_once('@'))
+    .filter(|(name, _)| !name.is_empty())
+    .map(|(name, version)| {
+      (
+        name.to_string(),
+        GlobalDependency {
+          version: version.to_string(),
+        },
+      )
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::tempdir;
+
+  #[test]
+  fn test_parse_bun_list() {
+    let output = "/home/user/.bun/install/global node_modules (2)\n├── @biomejs/biome@1.9.4\n└── typescript@5.4.5\n";
+
+    let versions = parse_bun_list(output);
+
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions["@biomejs/biome"].version, "1.9.4");
+    assert_eq!(versions["typescript"].version, "5.4.5");
+  }
+
+  #[test]
+  fn test_installed_versions() {
+    let dir = tempdir().unwrap();
+    std::fs::write(
+      dir.path().join(PACKAGE_JSON_FILENAME),
+      r#"{ "dependencies": { "typescript": "^5.0.0", "missing": "^1.0.0" } }"#,
+    )
+    .unwrap();
+    let package_dir = dir.path().join("node_modules").join("typescript");
+    std::fs::create_dir_all(&package_dir).unwrap();
+    std::fs::write(
+      package_dir.join(PACKAGE_JSON_FILENAME),
+      r#"{ "name": "typescript", "version": "5.4.5" }"#,
+    )
+    .unwrap();
+
+    let versions = installed_versions(dir.path()).unwrap();
+
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions["typescript"].version, "5.4.5");
+  }
+}
@@ -1,8 +1,16 @@
+pub(super) mod changelog;
+pub(super) mod config;
+pub(super) mod deprecations;
+pub(super) mod filters;
 pub(super) mod flags;
+pub(super) mod globals;
 pub(super) mod package_info;
 pub(super) mod package_json;
 pub(super) mod package_manager;
+pub(super) mod peers;
 pub(super) mod prompt;
 pub(super) mod registry;
+pub(super) mod report;
 pub(super) mod updater;
 pub(super) mod versions;
+pub(super) mod workspaces;
@@ -1,45 +1,140 @@
-use colored::Colorize;
+use chrono::{DateTime, Utc};
+use colored::{ColoredString, Colorize};
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fmt::Display;
 
-use nodejs_semver::Version;
+use super::{
+  package_json::DependencyType,
+  package_manager::PackageManager,
+  registry::{Advisory, Severity},
+  versions::{format_publish_time, SemverDiff},
+};
 
-use super::versions::normalize_version;
+/// Marker of a deprecated version.
+const DEPRECATED_LABEL: &str = "⚠ deprecated";
 
-#[derive(Debug)]
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PackageInfo {
+  #[serde(rename = "name")]
   pub pkg_name: String,
+  #[serde(rename = "current")]
   pub current_version: String,
+  #[serde(rename = "target")]
   pub latest_version: String,
+  pub diff: Option<SemverDiff>,
+  pub dep_type: Option<DependencyType>,
+  pub workspace: Option<String>,
+  /// Package manager owning a global package.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub manager: Option<PackageManager>,
+  /// Publish times of the current and target versions, known when the full packument was fetched.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub current_published_at: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub published_at: Option<DateTime<Utc>>,
+  /// Where to look for release notes, known when the full packument was fetched.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repository: Option<String>,
+  #[serde(skip)]
+  pub tarball: Option<String>,
+  /// Security advisories affecting the current version, with `--audit`.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub advisories: Vec<Advisory>,
+  /// Deprecation messages of the current and target versions.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub current_deprecated: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub deprecated: Option<String>,
+  /// Peer dependencies of the target version.
+  #[serde(skip)]
+  pub peer_dependencies: HashMap<String, String>,
+}
+
+impl PackageInfo {
+  /// Target version colored based on the semver difference with the current one.
+  pub fn colored_latest_version(&self) -> ColoredString {
+    match self.diff {
+      Some(SemverDiff::Major) => self.latest_version.bright_red().bold(),
+      Some(SemverDiff::Minor) => self.latest_version.bright_yellow().bold(),
+      _ => self.latest_version.bright_green().bold(),
+    }
+  }
+
+  /// Highest severity of the advisories affecting the current version.
+  pub fn severity(&self) -> Option<Severity> {
+    self
+      .advisories
+      .iter()
+      .map(|advisory| advisory.severity)
+      .max()
+  }
+
+  /// When the current version was published: `2024-01-10, 5 months ago`.
+  pub fn current_published(&self) -> Option<String> {
+    self
+      .current_published_at
+      .map(|published_at| format_publish_time(published_at, Utc::now()))
+  }
+
+  /// When the target version was published: `2024-06-01, 3 days ago`.
+  pub fn published(&self) -> Option<String> {
+    self
+      .published_at
+      .map(|published_at| format_publish_time(published_at, Utc::now()))
+  }
+}
+
+/// Title of the group of updates with the given semver difference.
+pub fn diff_group_title(diff: Option<SemverDiff>) -> ColoredString {
+  match diff {
+    Some(SemverDiff::Major) => "Major".bright_red().bold(),
+    Some(SemverDiff::Minor) => "Minor".bright_yellow().bold(),
+    Some(SemverDiff::Patch) => "Patch".bright_green().bold(),
+    Some(SemverDiff::Prerelease) => "Prerelease".bright_magenta().bold(),
+    None => "Other".bold(),
+  }
+}
+
+/// Severity of the advisories affecting a version: `⚠ high`.
+pub fn colored_severity(severity: Severity) -> ColoredString {
+  let label = format!("⚠ {severity}");
+  match severity {
+    Severity::Critical | Severity::High => label.bright_red().bold(),
+    Severity::Moderate => label.bright_yellow(),
+    Severity::Low | Severity::Info => label.dimmed(),
+  }
 }
 
 impl Display for PackageInfo {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    // Parse and normalize versions
-    let current_ver_result = Version::parse(normalize_version(&self.current_version));
-    let latest_ver_result = Version::parse(&self.latest_version);
-
-    // Handle parsing errors
-    let Ok(current_ver) = current_ver_result else {
-      return Err(std::fmt::Error);
-    };
-
-    let Ok(latest_ver) = latest_ver_result else {
-      return Err(std::fmt::Error);
-    };
-
-    // Determine color based on version comparison
-    let colored_latest_version = if latest_ver.major > current_ver.major {
-      self.latest_version.bright_red().bold()
-    } else if latest_ver.minor > current_ver.minor {
-      self.latest_version.bright_yellow().bold()
-    } else {
-      self.latest_version.bright_green().bold()
-    };
-
-    write!(
-      f,
-      "{}: {} → {}",
-      self.pkg_name, self.current_version, colored_latest_version
-    )
+    if let Some(workspace) = &self.workspace {
+      write!(f, "{} ", format!("[{workspace}]").dimmed())?;
+    }
+    if let Some(manager) = &self.manager {
+      write!(f, "{} ", format!("[{manager}]").dimmed())?;
+    }
+
+    write!(f, "{}: {}", self.pkg_name, self.current_version)?;
+    if let Some(severity) = self.severity() {
+      write!(f, " {}", colored_severity(severity))?;
+    }
+    if self.current_deprecated.is_some() {
+      write!(f, " {}", DEPRECATED_LABEL.bright_yellow())?;
+    }
+    if let Some(published) = self.current_published() {
+      write!(f, " {}", format!("({published})").dimmed())?;
+    }
+
+    write!(f, " → {}", self.colored_latest_version())?;
+    if self.deprecated.is_some() {
+      write!(f, " {}", DEPRECATED_LABEL.bright_yellow())?;
+    }
+    if let Some(published) = self.published() {
+      write!(f, " {}", format!("({published})").dimmed())?;
+    }
+
+    Ok(())
   }
 }
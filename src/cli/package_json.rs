@@ -1,20 +1,31 @@
-use anyhow::{format_err, Result};
+use anyhow::{format_err, Context, Result};
 use colored::Colorize;
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::ffi::OsStr;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
 
+use nodejs_semver::{Range, Version};
+use similar::TextDiff;
+
 use super::{
   flags::Flags,
   package_info::PackageInfo,
-  package_manager::{PackageManager, BUN_LOCK, NPM_LOCK, PNPM_LOCK, YARN_LOCK},
+  package_manager::{is_corepack_available, PackageManager, PackageManagerSpec, LOCK_FILES},
+  report::{print_status, OutputFormat},
+  versions::{update_spec, widen_range},
+  workspaces::{discover_workspaces, Workspace, WorkspacesField},
 };
 use crate::utils::{
   fs::{find_closest_file, read_json},
   hashmap::merge,
+  json::replace_string_value,
 };
 
 pub type PackageDependencies = HashMap<String, String>;
@@ -24,26 +35,164 @@ pub static PACKAGE_JSON_FILENAME: &str = "package.json";
 #[derive(Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PackageJson {
+  pub name: Option<String>,
   pub dependencies: Option<PackageDependencies>,
   pub dev_dependencies: Option<PackageDependencies>,
   pub optional_dependencies: Option<PackageDependencies>,
+  pub peer_dependencies: Option<PackageDependencies>,
+  /// npm overrides, only their plain version pins are checked.
+  #[serde(default, deserialize_with = "version_pins")]
+  pub overrides: Option<PackageDependencies>,
+  /// Yarn resolutions.
+  #[serde(default, deserialize_with = "version_pins")]
+  pub resolutions: Option<PackageDependencies>,
+  pub pnpm: Option<PnpmField>,
   pub package_manager: Option<String>,
+  pub workspaces: Option<WorkspacesField>,
+}
+
+/// The `pnpm` field of `package.json`.
+#[derive(Deserialize, Debug, Default)]
+pub struct PnpmField {
+  #[serde(default, deserialize_with = "version_pins")]
+  pub overrides: Option<PackageDependencies>,
+}
+
+impl PackageJson {
+  pub fn dependencies_of(&self, dep_type: DependencyType) -> Option<&PackageDependencies> {
+    match dep_type {
+      DependencyType::Dependencies => self.dependencies.as_ref(),
+      DependencyType::DevDependencies => self.dev_dependencies.as_ref(),
+      DependencyType::OptionalDependencies => self.optional_dependencies.as_ref(),
+      DependencyType::PeerDependencies => self.peer_dependencies.as_ref(),
+      DependencyType::Overrides => self.overrides.as_ref(),
+      DependencyType::Resolutions => self.resolutions.as_ref(),
+      DependencyType::PnpmOverrides => self.pnpm.as_ref()?.overrides.as_ref(),
+    }
+  }
+}
+
+/// Keep the entries of an overrides object pinning a version, leaving out the nested overrides
+/// and the `$name` references to a dependency.
+fn version_pins<'de, D>(deserializer: D) -> Result<Option<PackageDependencies>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let entries: Option<HashMap<String, serde_json::Value>> = Deserialize::deserialize(deserializer)?;
+
+  Ok(entries.map(|entries| {
+    entries
+      .into_iter()
+      .filter_map(|(key, value)| match value {
+        serde_json::Value::String(version) if !version.starts_with('$') => Some((key, version)),
+        _ => None,
+      })
+      .collect()
+  }))
+}
+
+/// The `package.json` section a dependency is declared in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyType {
+  Dependencies,
+  DevDependencies,
+  OptionalDependencies,
+  PeerDependencies,
+  Overrides,
+  Resolutions,
+  #[serde(rename = "pnpm.overrides")]
+  PnpmOverrides,
+}
+
+impl DependencyType {
+  /// Path of the section in `package.json`.
+  pub fn path(self) -> &'static [&'static str] {
+    match self {
+      DependencyType::Dependencies => &["dependencies"],
+      DependencyType::DevDependencies => &["devDependencies"],
+      DependencyType::OptionalDependencies => &["optionalDependencies"],
+      DependencyType::PeerDependencies => &["peerDependencies"],
+      DependencyType::Overrides => &["overrides"],
+      DependencyType::Resolutions => &["resolutions"],
+      DependencyType::PnpmOverrides => &["pnpm", "overrides"],
+    }
+  }
+
+  /// Whether the section lists packages installed in `node_modules`, rather than constraints on
+  /// the versions of other packages.
+  pub fn is_installed(self) -> bool {
+    matches!(
+      self,
+      DependencyType::Dependencies
+        | DependencyType::DevDependencies
+        | DependencyType::OptionalDependencies
+    )
+  }
+
+  /// Whether the section pins the version of a package anywhere in the dependency tree.
+  pub fn is_override(self) -> bool {
+    matches!(
+      self,
+      DependencyType::Overrides | DependencyType::Resolutions | DependencyType::PnpmOverrides
+    )
+  }
 }
 
-#[derive(Deserialize, Debug)]
-pub struct GlobalDependency {
+impl Display for DependencyType {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.path().join("."))
+  }
+}
+
+/// A dependency to check, along with the manifest section and workspace it belongs to.
+#[derive(Debug, Clone)]
+pub struct Dependency {
+  pub name: String,
   pub version: String,
+  pub dep_type: Option<DependencyType>,
+  pub workspace: Option<String>,
+  /// Package manager owning a global package.
+  pub manager: Option<PackageManager>,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct GlobalDependencies {
-  pub dependencies: HashMap<String, GlobalDependency>,
+impl Dependency {
+  /// Package to look up, overrides keys possibly selecting it by parent or version:
+  /// `**/lodash`, `react>scheduler`, `@types/node@<20`.
+  pub fn registry_name(&self) -> String {
+    if !self.dep_type.is_some_and(DependencyType::is_override) {
+      return self.name.clone();
+    }
+
+    // The last path segment, along with its scope
+    let key = self.name.rsplit('>').next().unwrap_or_default();
+    let segments: Vec<&str> = key.split('/').collect();
+    let name = match segments.as_slice() {
+      [.., scope, name] if scope.starts_with('@') => format!("{scope}/{name}"),
+      [.., name] => (*name).to_owned(),
+      [] => String::new(),
+    };
+
+    // Strip the version selector, past the scope
+    match name.get(1..).and_then(|rest| rest.find('@')) {
+      Some(index) => name[..=index].to_owned(),
+      None => name,
+    }
+  }
+
+  /// Whether a peer range already accepts `version`, leaving nothing to update.
+  pub fn accepts(&self, version: &str) -> bool {
+    self.dep_type == Some(DependencyType::PeerDependencies)
+      && Range::parse(&self.version)
+        .is_ok_and(|range| Version::parse(version).is_ok_and(|version| range.satisfies(&version)))
+  }
 }
 
 #[derive(Debug, Default)]
 pub struct PackageJsonManager {
   pub file_path: Option<PathBuf>,
   pub json: PackageJson,
+  pub workspaces: Vec<Workspace>,
   pub flags: Arc<Flags>,
 }
 
@@ -68,12 +217,14 @@ impl PackageJsonManager {
     })
   }
 
-  /// Call file reader to read `package.json` file.
+  /// Call file reader to read `package.json` file and the manifests of its workspace members.
   pub fn read(&mut self) -> Result<()> {
     match self.file_path.as_ref() {
-      Some(file_path) => read_json(file_path).map(|json| {
-        self.json = json;
-      }),
+      Some(file_path) => {
+        self.json = read_json(file_path)?;
+        self.workspaces = discover_workspaces(file_path, &self.json)?;
+        Ok(())
+      }
       None => Err(format_err!(
         "Couldn't find an available {} file.",
         PACKAGE_JSON_FILENAME
@@ -81,122 +232,347 @@ impl PackageJsonManager {
     }
   }
 
-  pub fn get_local_deps(&self) -> PackageDependencies {
-    let dependencies = if self.flags.production {
+  /// Dependencies of the root manifest followed by those of every workspace member.
+  pub fn get_local_deps(&self) -> Vec<Dependency> {
+    let root_deps = self.get_manifest_deps(&self.json, None);
+    let workspace_deps = self
+      .workspaces
+      .iter()
+      .flat_map(|workspace| self.get_manifest_deps(&workspace.json, Some(&workspace.name)));
+
+    root_deps.into_iter().chain(workspace_deps).collect()
+  }
+
+  fn get_manifest_deps(&self, json: &PackageJson, workspace: Option<&str>) -> Vec<Dependency> {
+    let dep_types = if self.flags.production {
       // Return production dependencies
       vec![
-        self.json.dependencies.as_ref(),
-        self.json.optional_dependencies.as_ref(),
+        DependencyType::Dependencies,
+        DependencyType::OptionalDependencies,
       ]
     } else if self.flags.development {
       // Return development dependencies
-      vec![self.json.dev_dependencies.as_ref()]
+      vec![DependencyType::DevDependencies]
     } else {
       // Default to all dependencies
       vec![
-        self.json.dependencies.as_ref(),
-        self.json.dev_dependencies.as_ref(),
-        self.json.optional_dependencies.as_ref(),
+        DependencyType::Dependencies,
+        DependencyType::DevDependencies,
+        DependencyType::OptionalDependencies,
       ]
     };
 
-    merge(&dependencies)
-  }
-
-  pub fn get_global_deps() -> Result<PackageDependencies> {
-    // Run the `npm list -g --depth=0` command
-    let output = Command::new("npm")
-      .args(["ls", "--json", "-g", "--depth=0"])
-      .output()?;
-
-    let global_deps: GlobalDependencies = serde_json::from_slice(&output.stdout)?;
-
-    // Map the dependencies to a name -> version structure
-    let packages = global_deps
-      .dependencies
+    // Tag every entry with its section, later sections win when a package is declared twice
+    let dependencies: Vec<Option<HashMap<String, Dependency>>> = dep_types
       .into_iter()
-      .map(|(name, package)| (name, package.version))
+      .map(|dep_type| {
+        json
+          .dependencies_of(dep_type)
+          .map(|deps| Self::section_deps(deps, dep_type, workspace).collect())
+      })
       .collect();
 
-    Ok(packages)
+    // Peer ranges and overrides constrain the installed packages, they're checked on their own
+    let mut constraint_types = Vec::new();
+    if self.flags.peer {
+      constraint_types.push(DependencyType::PeerDependencies);
+    }
+    if self.flags.overrides {
+      constraint_types.extend([
+        DependencyType::Overrides,
+        DependencyType::Resolutions,
+        DependencyType::PnpmOverrides,
+      ]);
+    }
+    let constraints = constraint_types.into_iter().flat_map(|dep_type| {
+      json
+        .dependencies_of(dep_type)
+        .into_iter()
+        .flat_map(move |deps| Self::section_deps(deps, dep_type, workspace))
+        .map(|(_, dependency)| dependency)
+    });
+
+    merge(&dependencies.iter().map(Option::as_ref).collect::<Vec<_>>())
+      .into_values()
+      .chain(constraints)
+      .collect()
+  }
+
+  fn section_deps<'a>(
+    deps: &'a PackageDependencies,
+    dep_type: DependencyType,
+    workspace: Option<&'a str>,
+  ) -> impl Iterator<Item = (String, Dependency)> + 'a {
+    deps.iter().map(move |(name, version)| {
+      let dependency = Dependency {
+        name: name.clone(),
+        version: version.clone(),
+        dep_type: Some(dep_type),
+        workspace: workspace.map(str::to_owned),
+        manager: None,
+      };
+      (name.clone(), dependency)
+    })
   }
 
   /// Detect the package manager based on the provided flags, package.json, and lock files.
-  fn detect_package_manager(&self) -> PackageManager {
-    if self.flags.global {
-      return PackageManager::Npm;
+  fn detect_package_manager(&self) -> Result<PackageManager> {
+    if let Some(manager) = self.flags.package_manager {
+      return Ok(manager);
     }
 
-    if let Some(manager) = self.get_package_manager_from_json() {
-      return manager;
+    if let Some(manager) = self.get_package_manager_from_json()? {
+      return Ok(manager);
     }
 
     if let Some(manager) = self.detect_lock_file() {
-      return manager;
+      return Ok(manager);
     }
 
-    PackageManager::Npm
+    Ok(PackageManager::Npm)
   }
 
-  fn get_package_manager_from_json(&self) -> Option<PackageManager> {
-    let package_manager = self.json.package_manager.as_ref()?.split('@').next()?;
-    Some(PackageManager::from(package_manager))
+  fn get_package_manager_from_json(&self) -> Result<Option<PackageManager>> {
+    Ok(self.declared_package_manager()?.map(|spec| spec.manager))
   }
 
-  fn detect_lock_file(&self) -> Option<PackageManager> {
-    let lock_files = [NPM_LOCK, YARN_LOCK, PNPM_LOCK, BUN_LOCK];
+  fn declared_package_manager(&self) -> Result<Option<PackageManagerSpec>> {
+    let Some(package_manager) = self.json.package_manager.as_deref() else {
+      return Ok(None);
+    };
+
+    PackageManagerSpec::parse(package_manager)
+      .map(Some)
+      .with_context(|| format!("Invalid \"packageManager\" field in {PACKAGE_JSON_FILENAME}"))
+  }
 
+  fn detect_lock_file(&self) -> Option<PackageManager> {
     // Ensure file_path exists before proceeding
     let file_path = self.file_path.as_ref()?;
 
     // Iterate over lock files and check existence
-    lock_files.iter().find_map(|&lock_file| {
+    LOCK_FILES.iter().find_map(|&lock_file| {
       let candidate_path = file_path.with_file_name(lock_file);
       if candidate_path.exists() {
-        Some(PackageManager::from_lock_file(lock_file))
+        PackageManager::from_lock_file(lock_file).ok()
       } else {
         None
       }
     })
   }
 
+  /// Apply the selected updates.
+  ///
+  /// Global packages are installed straight away by the package manager owning them, while local
+  /// updates are first written to their `package.json` and then installed with a single command,
+  /// unless `--no-install` is set.
   pub fn install_deps(&self, updates: &[PackageInfo]) -> Result<()> {
-    let package_manager = self.detect_package_manager();
-    let install_args = Self::construct_install_args(updates);
-    let command = PackageManager::determine_install_command(&package_manager);
+    if self.flags.global {
+      let mut updates_by_manager: BTreeMap<PackageManager, Vec<&PackageInfo>> = BTreeMap::new();
+      for update in updates {
+        updates_by_manager
+          .entry(update.manager.unwrap_or(PackageManager::Npm))
+          .or_default()
+          .push(update);
+      }
 
-    self.execute_install_command(&package_manager, command, install_args)?;
+      for (package_manager, updates) in updates_by_manager {
+        let mut args: Vec<String> = package_manager
+          .global_install_args()
+          .iter()
+          .map(|&arg| arg.to_owned())
+          .collect();
+        args.extend(Self::construct_install_args(&package_manager, &updates));
+        self.execute_install_command(&package_manager, args)?;
+      }
+    } else {
+      let package_manager = self.detect_package_manager()?;
+
+      let changes = self.manifest_changes(updates)?;
+      for change in &changes {
+        if self.flags.dry_run {
+          print_status(self.flags.format, change.diff().trim_end_matches('\n'));
+        } else {
+          fs::write(&change.file_path, &change.updated)?;
+        }
+      }
+
+      if self.flags.no_install {
+        if !self.flags.dry_run {
+          print_status(
+            self.flags.format,
+            format!("{PACKAGE_JSON_FILENAME} successfully updated!").bright_green(),
+          );
+        }
+        return Ok(());
+      }
+
+      let selectors = self.updated_workspaces(updates)?;
+      let selectors: Vec<&str> = selectors.iter().map(String::as_str).collect();
+      let args = package_manager.install_args(&selectors);
+      self.execute_install_command(&package_manager, args)?;
+    }
+
+    if self.flags.dry_run {
+      print_status(
+        self.flags.format,
+        "\nDry run, nothing was changed.".bright_yellow(),
+      );
+    } else {
+      print_status(
+        self.flags.format,
+        "Packages successfully updated!".bright_green(),
+      );
+    }
 
     Ok(())
   }
 
-  fn construct_install_args(updates: &[PackageInfo]) -> Vec<String> {
+  /// Rewrite the ranges of the updated packages in the manifests declaring them, without writing them.
+  fn manifest_changes(&self, updates: &[PackageInfo]) -> Result<Vec<ManifestChange>> {
+    let mut updates_by_workspace: BTreeMap<Option<&str>, Vec<&PackageInfo>> = BTreeMap::new();
+    for update in updates {
+      updates_by_workspace
+        .entry(update.workspace.as_deref())
+        .or_default()
+        .push(update);
+    }
+
+    let mut changes = Vec::new();
+    for (workspace, updates) in updates_by_workspace {
+      let file_path = match workspace {
+        Some(name) => &self.find_workspace(name)?.file_path,
+        None => self.file_path.as_ref().ok_or_else(|| {
+          format_err!("Couldn't find an available {} file.", PACKAGE_JSON_FILENAME)
+        })?,
+      };
+
+      let original = fs::read_to_string(file_path)?;
+      let mut contents = original.clone();
+      for update in updates {
+        let Some(dep_type) = update.dep_type else {
+          continue;
+        };
+
+        // Peer ranges keep accepting the current versions, consumers may not have updated yet
+        let range = if dep_type == DependencyType::PeerDependencies {
+          widen_range(&update.current_version, &update.latest_version)
+        } else {
+          update_spec(&update.current_version, &update.latest_version)
+        };
+        let mut path = dep_type.path().to_vec();
+        path.push(&update.pkg_name);
+        contents = replace_string_value(&contents, &path, &range)?;
+      }
+
+      changes.push(ManifestChange {
+        file_path: file_path.clone(),
+        original,
+        updated: contents,
+      });
+    }
+
+    Ok(changes)
+  }
+
+  /// Selectors of the workspaces to install, none when the root manifest changed.
+  fn updated_workspaces(&self, updates: &[PackageInfo]) -> Result<Vec<String>> {
+    let mut selectors = Vec::new();
+    for update in updates {
+      let Some(name) = update.workspace.as_deref() else {
+        // The root manifest changed, install everything
+        return Ok(Vec::new());
+      };
+
+      let selector = self.find_workspace(name)?.selector();
+      if !selectors.contains(&selector) {
+        selectors.push(selector);
+      }
+    }
+
+    Ok(selectors)
+  }
+
+  fn find_workspace(&self, name: &str) -> Result<&Workspace> {
+    self
+      .workspaces
+      .iter()
+      .find(|workspace| workspace.name == name)
+      .ok_or_else(|| format_err!("Couldn't find workspace {}.", name))
+  }
+
+  fn construct_install_args(
+    package_manager: &PackageManager,
+    updates: &[&PackageInfo],
+  ) -> Vec<String> {
     updates
       .iter()
-      .map(|package| format!("{}@{}", package.pkg_name, package.latest_version))
+      .map(|package| package_manager.package_arg(&package.pkg_name, &package.latest_version))
       .collect()
   }
 
-  fn execute_install_command(
+  fn build_install_command<S: AsRef<OsStr>>(
     &self,
     package_manager: &PackageManager,
-    command: &str,
-    install_args: Vec<String>,
+    args: &[S],
+  ) -> Command {
+    // Global packages are installed by the manager owning them, regardless of the version the
+    // project declares
+    let declared = self
+      .declared_package_manager()
+      .ok()
+      .flatten()
+      .filter(|spec| !self.flags.global && spec.manager == *package_manager);
+
+    let use_corepack = declared.as_ref().is_some_and(|spec| {
+      spec.version.is_some() && package_manager.supports_corepack() && is_corepack_available()
+    });
+    if let (Some(spec), false) = (&declared, use_corepack) {
+      spec.warn_on_version_mismatch();
+    }
+
+    let mut cmd = package_manager.command(declared.as_ref(), use_corepack);
+    cmd.args(args);
+
+    // Run from the project root, the manifest may have been located from a subdirectory
+    if let Some(root_dir) = self.file_path.as_ref().and_then(|path| path.parent()) {
+      cmd.current_dir(root_dir);
+    }
+
+    cmd
+  }
+
+  fn execute_install_command<S: AsRef<OsStr>>(
+    &self,
+    package_manager: &PackageManager,
+    args: Vec<S>,
   ) -> Result<()> {
-    let mut cmd = Command::new(package_manager.to_str());
-    cmd.arg(command).args(install_args);
+    let mut cmd = self.build_install_command(package_manager, &args);
 
-    if self.flags.global {
-      cmd.arg("-g");
+    if self.flags.dry_run {
+      print_status(
+        self.flags.format,
+        format!("{} {}", "Would run:".bold(), format_command(&cmd)),
+      );
+      return Ok(());
     }
 
+    // The package manager output goes along with the progress messages, after the report
+    if self
+      .flags
+      .format
+      .is_some_and(OutputFormat::is_machine_readable)
+    {
+      cmd.stdout(io::stderr());
+    }
     let status = cmd.status()?;
-    if status.success() {
-      println!("{}", "Packages successfully updated!".bright_green());
-    } else {
+    if !status.success() {
       anyhow::bail!(
         "Failed to update packages using {} command for manager: {}",
-        command,
+        args
+          .first()
+          .map(|arg| arg.as_ref().to_string_lossy())
+          .unwrap_or_default(),
         package_manager
       );
     }
@@ -205,6 +581,48 @@ impl PackageJsonManager {
   }
 }
 
+/// New contents of a manifest.
+#[derive(Debug)]
+pub struct ManifestChange {
+  pub file_path: PathBuf,
+  pub original: String,
+  pub updated: String,
+}
+
+impl ManifestChange {
+  /// Unified diff between the original and the updated manifest.
+  pub fn diff(&self) -> String {
+    let path = self.file_path.display().to_string();
+    TextDiff::from_lines(&self.original, &self.updated)
+      .unified_diff()
+      .header(&path, &path)
+      .to_string()
+  }
+}
+
+/// Shell-like rendering of a command, with its working directory.
+fn format_command(cmd: &Command) -> String {
+  let quote = |arg: &OsStr| {
+    let arg = arg.to_string_lossy();
+    if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "'\"$`\\<>|&;*?".contains(c)) {
+      format!("'{}'", arg.replace('\'', "'\\''"))
+    } else {
+      arg.into_owned()
+    }
+  };
+
+  let command = std::iter::once(cmd.get_program())
+    .chain(cmd.get_args())
+    .map(quote)
+    .collect::<Vec<_>>()
+    .join(" ");
+
+  match cmd.get_current_dir() {
+    Some(dir) => format!("{command} (in {})", dir.display()),
+    None => command,
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -230,6 +648,68 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_constraint_sections() {
+    let package_json_raw = r#"
+    {
+      "peerDependencies": { "react": "^18.0.0" },
+      "overrides": { "semver": "7.6.0", "foo": { "bar": "1.0.0" }, "react": "$react" },
+      "resolutions": { "**/lodash": "4.17.20" },
+      "pnpm": { "overrides": { "foo@1>@types/node@<20": "20.0.0" } }
+    }
+  "#;
+
+    let json = serde_json::from_str::<PackageJson>(package_json_raw).unwrap();
+    let manager = PackageJsonManager {
+      json,
+      flags: Arc::new(Flags {
+        peer: true,
+        overrides: true,
+        ..Default::default()
+      }),
+      ..Default::default()
+    };
+
+    let mut deps: Vec<(String, String, String)> = manager
+      .get_local_deps()
+      .into_iter()
+      .map(|dep| {
+        (
+          dep.dep_type.unwrap().to_string(),
+          dep.registry_name(),
+          dep.version,
+        )
+      })
+      .collect();
+    deps.sort();
+
+    assert_eq!(
+      deps,
+      [
+        (
+          "overrides".to_owned(),
+          "semver".to_owned(),
+          "7.6.0".to_owned()
+        ),
+        (
+          "peerDependencies".to_owned(),
+          "react".to_owned(),
+          "^18.0.0".to_owned()
+        ),
+        (
+          "pnpm.overrides".to_owned(),
+          "@types/node".to_owned(),
+          "20.0.0".to_owned()
+        ),
+        (
+          "resolutions".to_owned(),
+          "lodash".to_owned(),
+          "4.17.20".to_owned()
+        ),
+      ]
+    );
+  }
+
   #[test]
   fn test_detect_package_manager_from_json() {
     let package_json = PackageJson {
@@ -242,7 +722,31 @@ mod tests {
       ..Default::default()
     };
 
-    assert_eq!(manager.detect_package_manager(), PackageManager::Pnpm);
+    assert_eq!(
+      manager.detect_package_manager().unwrap(),
+      PackageManager::Pnpm
+    );
+  }
+
+  #[test]
+  fn test_detect_unknown_package_manager() {
+    let mut manager = PackageJsonManager {
+      json: PackageJson {
+        package_manager: Some("pnmp@9.10.0".to_owned()),
+        ..Default::default()
+      },
+      ..Default::default()
+    };
+    assert!(manager.detect_package_manager().is_err());
+
+    manager.flags = Arc::new(Flags {
+      package_manager: Some(PackageManager::Deno),
+      ..Default::default()
+    });
+    assert_eq!(
+      manager.detect_package_manager().unwrap(),
+      PackageManager::Deno
+    );
   }
 
   #[test]
@@ -262,6 +766,146 @@ mod tests {
       ..Default::default()
     };
 
-    assert_eq!(manager.detect_package_manager(), PackageManager::Pnpm);
+    assert_eq!(
+      manager.detect_package_manager().unwrap(),
+      PackageManager::Pnpm
+    );
+  }
+
+  #[test]
+  fn test_manifest_changes_preserve_formatting() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join(PACKAGE_JSON_FILENAME);
+    let contents = "{\n\t\"devDependencies\": {\"typescript\": \"~5.4.0\"},\n\t\"dependencies\": {\n\t\t\"react\": \"^18.2.0\"\n\t}\n}\n";
+    std::fs::write(&file_path, contents).unwrap();
+
+    let manager = PackageJsonManager {
+      file_path: Some(file_path.clone()),
+      ..Default::default()
+    };
+
+    let updates = [
+      PackageInfo {
+        pkg_name: "react".to_owned(),
+        current_version: "^18.2.0".to_owned(),
+        latest_version: "19.0.0".to_owned(),
+        dep_type: Some(DependencyType::Dependencies),
+        ..Default::default()
+      },
+      PackageInfo {
+        pkg_name: "typescript".to_owned(),
+        current_version: "~5.4.0".to_owned(),
+        latest_version: "5.4.5".to_owned(),
+        dep_type: Some(DependencyType::DevDependencies),
+        ..Default::default()
+      },
+    ];
+    let changes = manager.manifest_changes(&updates).unwrap();
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].file_path, file_path);
+    assert_eq!(
+      changes[0].updated,
+      contents
+        .replace("^18.2.0", "^19.0.0")
+        .replace("~5.4.0", "~5.4.5")
+    );
+    // Nothing is written until the changes are applied
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), contents);
+
+    let diff = changes[0].diff();
+    assert!(diff.contains("-\t\t\"react\": \"^18.2.0\"\n+\t\t\"react\": \"^19.0.0\"\n"));
+  }
+
+  #[test]
+  fn test_manifest_changes_widen_peers_and_nested_overrides() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join(PACKAGE_JSON_FILENAME);
+    let contents = r#"{
+  "peerDependencies": { "react": "^18.0.0" },
+  "pnpm": { "overrides": { "**/lodash": "4.17.20" } }
+}
+"#;
+    std::fs::write(&file_path, contents).unwrap();
+
+    let manager = PackageJsonManager {
+      file_path: Some(file_path.clone()),
+      ..Default::default()
+    };
+
+    let updates = [
+      PackageInfo {
+        pkg_name: "react".to_owned(),
+        current_version: "^18.0.0".to_owned(),
+        latest_version: "19.0.0".to_owned(),
+        dep_type: Some(DependencyType::PeerDependencies),
+        ..Default::default()
+      },
+      PackageInfo {
+        pkg_name: "**/lodash".to_owned(),
+        current_version: "4.17.20".to_owned(),
+        latest_version: "4.17.21".to_owned(),
+        dep_type: Some(DependencyType::PnpmOverrides),
+        ..Default::default()
+      },
+    ];
+    let changes = manager.manifest_changes(&updates).unwrap();
+
+    assert_eq!(
+      changes[0].updated,
+      contents
+        .replace("^18.0.0", "^18.0.0 || ^19.0.0")
+        .replace("4.17.20", "4.17.21")
+    );
+  }
+
+  #[test]
+  fn test_updated_workspaces() {
+    let manager = PackageJsonManager {
+      workspaces: vec![
+        Workspace {
+          name: "web".to_owned(),
+          json: serde_json::from_str(r#"{ "name": "web" }"#).unwrap(),
+          ..Default::default()
+        },
+        Workspace {
+          name: "packages/b".to_owned(),
+          ..Default::default()
+        },
+      ],
+      ..Default::default()
+    };
+    let update = |workspace: Option<&str>| PackageInfo {
+      pkg_name: "react".to_owned(),
+      workspace: workspace.map(str::to_owned),
+      ..Default::default()
+    };
+
+    let members = manager
+      .updated_workspaces(&[
+        update(Some("web")),
+        update(Some("packages/b")),
+        update(Some("web")),
+      ])
+      .unwrap();
+    let root = manager
+      .updated_workspaces(&[update(Some("web")), update(None)])
+      .unwrap();
+
+    assert_eq!(members, ["web", "./packages/b"]);
+    assert!(root.is_empty());
+  }
+
+  #[test]
+  fn test_format_command() {
+    let mut cmd = Command::new("pnpm");
+    cmd
+      .args(["install", "--filter", "@scope/web app"])
+      .current_dir("/project");
+
+    assert_eq!(
+      format_command(&cmd),
+      "pnpm install --filter '@scope/web app' (in /project)"
+    );
   }
 }
@@ -1,65 +1,326 @@
+use clap::ValueEnum;
+use colored::Colorize;
+use nodejs_semver::Version;
+use serde::Serialize;
 use std::fmt::{Display, Formatter};
+use std::process::{Command, Stdio};
+use thiserror::Error;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PackageManager {
   Npm,
   Yarn,
   Pnpm,
   Bun,
+  Deno,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PackageManagerError {
+  #[error("Unknown package manager \"{0}\", expected npm, yarn, pnpm, bun or deno. Use --package-manager to choose one.")]
+  Unknown(String),
+  #[error("Unknown lock file {0}")]
+  UnknownLockFile(String),
 }
 
 const NPM: &str = "npm";
 const YARN: &str = "yarn";
 const PNPM: &str = "pnpm";
 const BUN: &str = "bun";
+const DENO: &str = "deno";
+const COREPACK: &str = "corepack";
 
 pub const NPM_LOCK: &str = "package-lock.json";
 pub const YARN_LOCK: &str = "yarn.lock";
 pub const PNPM_LOCK: &str = "pnpm-lock.yaml";
-pub const BUN_LOCK: &str = "bun.lockb";
+pub const BUN_LOCK: &str = "bun.lock";
+/// Binary lock file of bun before 1.2.
+pub const BUN_LOCKB: &str = "bun.lockb";
+pub const DENO_LOCK: &str = "deno.lock";
+
+/// Lock files in detection order.
+pub const LOCK_FILES: [&str; 6] = [
+  NPM_LOCK, YARN_LOCK, PNPM_LOCK, BUN_LOCK, BUN_LOCKB, DENO_LOCK,
+];
 
 impl PackageManager {
-  pub fn from_lock_file(lock_file: &str) -> Self {
+  pub fn from_lock_file(lock_file: &str) -> Result<Self, PackageManagerError> {
     match lock_file {
-      NPM_LOCK => Self::Npm,
-      YARN_LOCK => Self::Yarn,
-      PNPM_LOCK => Self::Pnpm,
-      BUN_LOCK => Self::Bun,
-      _ => unreachable!(),
+      NPM_LOCK => Ok(Self::Npm),
+      YARN_LOCK => Ok(Self::Yarn),
+      PNPM_LOCK => Ok(Self::Pnpm),
+      BUN_LOCK | BUN_LOCKB => Ok(Self::Bun),
+      DENO_LOCK => Ok(Self::Deno),
+      _ => Err(PackageManagerError::UnknownLockFile(lock_file.to_string())),
     }
   }
 
-  pub fn to_str(&self) -> &'static str {
+  pub fn to_str(self) -> &'static str {
     match self {
       Self::Npm => NPM,
       Self::Yarn => YARN,
       Self::Pnpm => PNPM,
       Self::Bun => BUN,
+      Self::Deno => DENO,
+    }
+  }
+
+  /// Whether corepack can run a given version of this package manager.
+  pub fn supports_corepack(&self) -> bool {
+    matches!(self, Self::Npm | Self::Yarn | Self::Pnpm)
+  }
+
+  /// Command running this package manager, through corepack at the declared version when possible.
+  pub fn command(&self, declared: Option<&PackageManagerSpec>, use_corepack: bool) -> Command {
+    match declared {
+      Some(spec)
+        if use_corepack
+          && self.supports_corepack()
+          && spec.manager == *self
+          && spec.version.is_some() =>
+      {
+        let mut cmd = Command::new(COREPACK);
+        cmd.arg(spec.to_string());
+        cmd
+      }
+      _ => Command::new(self.to_str()),
     }
   }
 
-  pub fn determine_install_command(&self) -> &str {
+  /// Version of the binary found on `PATH`.
+  pub fn installed_version(&self) -> Option<Version> {
+    let output = Command::new(self.to_str())
+      .arg("--version")
+      .stderr(Stdio::null())
+      .output()
+      .ok()?;
+
+    // Deno prints `deno 2.0.0 (stable, release, …)` followed by the V8 and TypeScript versions
+    String::from_utf8_lossy(&output.stdout)
+      .lines()
+      .next()?
+      .split_whitespace()
+      .find_map(|word| Version::parse(word).ok())
+  }
+
+  /// Arguments installing packages globally, followed by the packages.
+  pub fn global_install_args(&self) -> &'static [&'static str] {
     match self {
-      PackageManager::Npm => "install",
-      _ => "add",
+      PackageManager::Npm => &["install", "--global"],
+      PackageManager::Yarn => &["global", "add"],
+      PackageManager::Pnpm | PackageManager::Bun => &["add", "--global"],
+      PackageManager::Deno => &["install", "--global"],
+    }
+  }
+
+  /// Argument installing a package at a version, deno needing the `npm:` prefix.
+  pub fn package_arg(&self, name: &str, version: &str) -> String {
+    match self {
+      PackageManager::Deno => format!("npm:{name}@{version}"),
+      _ => format!("{name}@{version}"),
+    }
+  }
+
+  /// Arguments installing the project dependencies, scoped to the given workspaces when supported.
+  pub fn install_args<'a>(&self, workspaces: &[&'a str]) -> Vec<&'a str> {
+    let filter_flag = match self {
+      PackageManager::Npm => Some("--workspace"),
+      PackageManager::Pnpm | PackageManager::Bun => Some("--filter"),
+      // Yarn can't install a subset of the workspaces without plugins, nor can deno
+      PackageManager::Yarn | PackageManager::Deno => None,
+    };
+
+    let mut args = vec!["install"];
+    if let Some(filter_flag) = filter_flag {
+      for workspace in workspaces {
+        args.extend([filter_flag, workspace]);
+      }
+    }
+
+    args
+  }
+}
+
+/// Whether `corepack` is available to run the package manager version declared in `package.json`.
+pub fn is_corepack_available() -> bool {
+  Command::new(COREPACK)
+    .arg("--version")
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .status()
+    .is_ok_and(|status| status.success())
+}
+
+/// The `packageManager` field of `package.json`: `pnpm@9.1.0+sha256.…`.
+#[derive(Debug, PartialEq)]
+pub struct PackageManagerSpec {
+  pub manager: PackageManager,
+  pub version: Option<Version>,
+  /// Integrity suffix checked by corepack: `+sha256.…`.
+  pub hash: Option<String>,
+}
+
+impl PackageManagerSpec {
+  pub fn parse(field: &str) -> Result<Self, PackageManagerError> {
+    let (name, reference) = field.split_once('@').unwrap_or((field, ""));
+    let (version, hash) = match reference.split_once('+') {
+      Some((version, hash)) => (version, Some(format!("+{hash}"))),
+      None => (reference, None),
+    };
+
+    Ok(Self {
+      manager: PackageManager::try_from(name)?,
+      version: Version::parse(version).ok(),
+      hash,
+    })
+  }
+
+  /// Warn when the binary on `PATH` has another major version than the declared one.
+  pub fn warn_on_version_mismatch(&self) {
+    let (Some(declared), Some(installed)) = (&self.version, self.manager.installed_version())
+    else {
+      return;
+    };
+
+    if declared.major != installed.major {
+      eprintln!(
+        "{}",
+        format!(
+          "⚠️  package.json declares {self} but {} {installed} is installed, the lockfile may be rewritten in an incompatible format. Enable corepack to use the declared version.",
+          self.manager
+        )
+        .bright_yellow()
+      );
     }
   }
 }
 
+impl Display for PackageManagerSpec {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.manager)?;
+    if let Some(version) = &self.version {
+      write!(f, "@{version}{}", self.hash.as_deref().unwrap_or_default())?;
+    }
+    Ok(())
+  }
+}
+
 impl Display for PackageManager {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     write!(f, "{}", self.to_str())
   }
 }
 
-impl From<&str> for PackageManager {
-  fn from(s: &str) -> Self {
+impl TryFrom<&str> for PackageManager {
+  type Error = PackageManagerError;
+
+  fn try_from(s: &str) -> Result<Self, Self::Error> {
     match s {
-      NPM => PackageManager::Npm,
-      YARN => PackageManager::Yarn,
-      PNPM => PackageManager::Pnpm,
-      BUN => PackageManager::Bun,
-      _ => unreachable!(),
+      NPM => Ok(PackageManager::Npm),
+      YARN => Ok(PackageManager::Yarn),
+      PNPM => Ok(PackageManager::Pnpm),
+      BUN => Ok(PackageManager::Bun),
+      DENO => Ok(PackageManager::Deno),
+      _ => Err(PackageManagerError::Unknown(s.to_string())),
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_install_args() {
+    assert_eq!(PackageManager::Npm.install_args(&[]), ["install"]);
+    assert_eq!(
+      PackageManager::Npm.install_args(&["web", "api"]),
+      ["install", "--workspace", "web", "--workspace", "api"]
+    );
+    assert_eq!(
+      PackageManager::Pnpm.install_args(&["web"]),
+      ["install", "--filter", "web"]
+    );
+    assert_eq!(
+      PackageManager::Bun.install_args(&["web"]),
+      ["install", "--filter", "web"]
+    );
+    assert_eq!(
+      PackageManager::Pnpm.install_args(&["./packages/b"]),
+      ["install", "--filter", "./packages/b"]
+    );
+    assert_eq!(PackageManager::Yarn.install_args(&["web"]), ["install"]);
+    assert_eq!(PackageManager::Deno.install_args(&["web"]), ["install"]);
+  }
+
+  #[test]
+  fn test_parse_package_manager_spec() {
+    let spec = PackageManagerSpec::parse("pnpm@9.1.0+sha256.abc123").unwrap();
+    assert_eq!(spec.manager, PackageManager::Pnpm);
+    assert_eq!(spec.version, Version::parse("9.1.0").ok());
+    assert_eq!(spec.to_string(), "pnpm@9.1.0+sha256.abc123");
+
+    let spec = PackageManagerSpec::parse("yarn").unwrap();
+    assert_eq!(spec.manager, PackageManager::Yarn);
+    assert_eq!(spec.version, None);
+
+    assert_eq!(
+      PackageManagerSpec::parse("deno@2.1.0").unwrap().manager,
+      PackageManager::Deno
+    );
+    assert_eq!(
+      PackageManagerSpec::parse("pnmp@9.1.0"),
+      Err(PackageManagerError::Unknown("pnmp".to_string()))
+    );
+  }
+
+  #[test]
+  fn test_from_lock_file() {
+    assert_eq!(
+      PackageManager::from_lock_file(BUN_LOCK),
+      Ok(PackageManager::Bun)
+    );
+    assert_eq!(
+      PackageManager::from_lock_file(BUN_LOCKB),
+      Ok(PackageManager::Bun)
+    );
+    assert_eq!(
+      PackageManager::from_lock_file(DENO_LOCK),
+      Ok(PackageManager::Deno)
+    );
+    assert!(PackageManager::from_lock_file("Cargo.lock").is_err());
+  }
+
+  #[test]
+  fn test_command_through_corepack() {
+    let spec = PackageManagerSpec::parse("pnpm@9.1.0").unwrap();
+    let program = |cmd: &Command| {
+      std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(std::ffi::OsStr::to_string_lossy)
+        .collect::<Vec<_>>()
+        .join(" ")
+    };
+
+    assert_eq!(
+      program(&PackageManager::Pnpm.command(Some(&spec), true)),
+      "corepack pnpm@9.1.0"
+    );
+    assert_eq!(
+      program(&PackageManager::Pnpm.command(Some(&spec), false)),
+      "pnpm"
+    );
+    assert_eq!(
+      program(&PackageManager::Npm.command(Some(&spec), true)),
+      "npm"
+    );
+    assert_eq!(program(&PackageManager::Npm.command(None, true)), "npm");
+
+    let spec = PackageManagerSpec::parse("bun@1.1.0").unwrap();
+    assert_eq!(
+      program(&PackageManager::Bun.command(Some(&spec), true)),
+      "bun"
+    );
+  }
+}
@@ -0,0 +1,271 @@
+use nodejs_semver::{Range, Version};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::{package_info::PackageInfo, versions::current_version_of};
+
+/// A checked dependency, with the peer dependencies of its current version.
+#[derive(Debug, Clone)]
+pub struct PeerDependent {
+  pub name: String,
+  pub workspace: Option<String>,
+  pub current_version: String,
+  pub peer_dependencies: HashMap<String, String>,
+}
+
+/// A package whose peer dependency range doesn't accept the version another one would have.
+#[derive(Debug, PartialEq)]
+pub struct PeerConflict {
+  pub workspace: Option<String>,
+  pub dependent: String,
+  pub dependent_version: String,
+  pub peer: String,
+  pub range: String,
+  pub peer_version: String,
+}
+
+impl Display for PeerConflict {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if let Some(workspace) = &self.workspace {
+      write!(f, "[{workspace}] ")?;
+    }
+
+    write!(
+      f,
+      "{}@{} requires {}@{}, but {} would be {}",
+      self.dependent, self.dependent_version, self.peer, self.range, self.peer, self.peer_version
+    )
+  }
+}
+
+/// Outcome of the peer dependencies check of a selection.
+#[derive(Debug, Default)]
+pub struct PeerCheck {
+  /// Available updates added to the selection to satisfy the peer dependencies.
+  pub added: Vec<PackageInfo>,
+  /// Conflicts no available update resolves.
+  pub conflicts: Vec<PeerConflict>,
+}
+
+/// Check the peer dependencies of the selected updates against the other dependencies of their
+/// manifest, adding the available updates that resolve the conflicts to the selection.
+pub fn check_peer_dependencies(
+  selected: &mut Vec<PackageInfo>,
+  available: &[PackageInfo],
+  dependents: &[PeerDependent],
+) -> PeerCheck {
+  let mut added = Vec::new();
+
+  loop {
+    let conflicts = find_conflicts(selected, dependents);
+    let fixes: Vec<&PackageInfo> = available
+      .iter()
+      .filter(|update| !selected.iter().any(|package| is_same(package, update)))
+      .filter(|update| {
+        conflicts
+          .iter()
+          .any(|conflict| resolves(update, conflict, selected))
+      })
+      .collect();
+
+    if fixes.is_empty() {
+      return PeerCheck { added, conflicts };
+    }
+
+    for fix in fixes {
+      selected.push(fix.clone());
+      added.push(fix.clone());
+    }
+  }
+}
+
+/// Conflicts involving at least one selected update, those already in the manifest being left alone.
+fn find_conflicts(selected: &[PackageInfo], dependents: &[PeerDependent]) -> Vec<PeerConflict> {
+  let resolved = resolved_versions(selected, dependents);
+
+  let mut conflicts = Vec::new();
+  for dependent in dependents {
+    let update = selected.iter().find(|package| {
+      package.pkg_name == dependent.name && package.workspace == dependent.workspace
+    });
+    let (dependent_version, peer_dependencies) = match update {
+      Some(update) => (update.latest_version.clone(), &update.peer_dependencies),
+      None => (
+        dependent.current_version.clone(),
+        &dependent.peer_dependencies,
+      ),
+    };
+
+    for (peer, range) in peer_dependencies {
+      let Some((peer_version, peer_updated)) = resolved.get(&(&dependent.workspace, peer)) else {
+        continue;
+      };
+      if update.is_none() && !peer_updated {
+        continue;
+      }
+
+      if !satisfies(peer_version, range) {
+        conflicts.push(PeerConflict {
+          workspace: dependent.workspace.clone(),
+          dependent: dependent.name.clone(),
+          dependent_version: dependent_version.clone(),
+          peer: peer.clone(),
+          range: range.clone(),
+          peer_version: peer_version.to_string(),
+        });
+      }
+    }
+  }
+
+  conflicts
+}
+
+/// Version of every dependency once the selection is installed, and whether it is updated.
+fn resolved_versions<'a>(
+  selected: &[PackageInfo],
+  dependents: &'a [PeerDependent],
+) -> HashMap<(&'a Option<String>, &'a String), (Version, bool)> {
+  dependents
+    .iter()
+    .filter_map(|dependent| {
+      let update = selected.iter().find(|package| {
+        package.pkg_name == dependent.name && package.workspace == dependent.workspace
+      });
+      let version = match update {
+        Some(update) => Version::parse(&update.latest_version).ok()?,
+        None => current_version_of(&dependent.current_version).ok()?,
+      };
+
+      Some((
+        (&dependent.workspace, &dependent.name),
+        (version, update.is_some()),
+      ))
+    })
+    .collect()
+}
+
+/// Whether selecting an update resolves a conflict, as the dependent or as the peer.
+fn resolves(update: &PackageInfo, conflict: &PeerConflict, selected: &[PackageInfo]) -> bool {
+  if update.workspace != conflict.workspace {
+    return false;
+  }
+
+  if update.pkg_name == conflict.dependent {
+    return update
+      .peer_dependencies
+      .get(&conflict.peer)
+      .is_none_or(|range| {
+        Version::parse(&conflict.peer_version).is_ok_and(|version| satisfies(&version, range))
+      });
+  }
+
+  // Only a peer left at its current version can still move
+  let peer_is_selected = selected
+    .iter()
+    .any(|package| package.pkg_name == conflict.peer && package.workspace == conflict.workspace);
+  update.pkg_name == conflict.peer
+    && !peer_is_selected
+    && Version::parse(&update.latest_version)
+      .is_ok_and(|version| satisfies(&version, &conflict.range))
+}
+
+fn satisfies(version: &Version, range: &str) -> bool {
+  // Unparsable ranges can't be checked, they don't block the update
+  Range::parse(range).map_or(true, |range| range.satisfies(version))
+}
+
+fn is_same(a: &PackageInfo, b: &PackageInfo) -> bool {
+  a.pkg_name == b.pkg_name && a.workspace == b.workspace
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn dependent(name: &str, version: &str, peers: &[(&str, &str)]) -> PeerDependent {
+    PeerDependent {
+      name: name.to_string(),
+      workspace: None,
+      current_version: version.to_string(),
+      peer_dependencies: peers
+        .iter()
+        .map(|(peer, range)| ((*peer).to_string(), (*range).to_string()))
+        .collect(),
+    }
+  }
+
+  fn update(name: &str, version: &str, peers: &[(&str, &str)]) -> PackageInfo {
+    PackageInfo {
+      pkg_name: name.to_string(),
+      latest_version: version.to_string(),
+      peer_dependencies: peers
+        .iter()
+        .map(|(peer, range)| ((*peer).to_string(), (*range).to_string()))
+        .collect(),
+      ..Default::default()
+    }
+  }
+
+  fn dependents() -> Vec<PeerDependent> {
+    vec![
+      dependent("react", "^18.3.1", &[]),
+      dependent("react-dom", "^18.3.1", &[("react", "^18.3.1")]),
+      dependent("react-modal", "^3.16.1", &[("react", "^16 || ^17 || ^18")]),
+    ]
+  }
+
+  #[test]
+  fn test_adds_updates_resolving_conflicts() {
+    let available = [
+      update("react", "19.0.0", &[]),
+      update("react-dom", "19.0.0", &[("react", "^19.0.0")]),
+    ];
+    let mut selected = vec![available[0].clone()];
+
+    let check = check_peer_dependencies(&mut selected, &available, &dependents());
+
+    assert_eq!(check.added.len(), 1);
+    assert_eq!(check.added[0].pkg_name, "react-dom");
+    assert_eq!(selected.len(), 2);
+    assert_eq!(
+      check.conflicts,
+      [PeerConflict {
+        workspace: None,
+        dependent: "react-modal".to_string(),
+        dependent_version: "^3.16.1".to_string(),
+        peer: "react".to_string(),
+        range: "^16 || ^17 || ^18".to_string(),
+        peer_version: "19.0.0".to_string(),
+      }]
+    );
+  }
+
+  #[test]
+  fn test_adds_peer_required_by_selected_update() {
+    let available = [
+      update("react", "19.0.0", &[]),
+      update("react-dom", "19.0.0", &[("react", "^19.0.0")]),
+    ];
+    let mut selected = vec![available[1].clone()];
+
+    let check = check_peer_dependencies(&mut selected, &available, &dependents()[..2]);
+
+    assert_eq!(check.added[0].pkg_name, "react");
+    assert!(check.conflicts.is_empty());
+  }
+
+  #[test]
+  fn test_ignores_existing_conflicts() {
+    let dependents = [
+      dependent("react", "^17.0.0", &[]),
+      dependent("react-dom", "^18.3.1", &[("react", "^18.3.1")]),
+      dependent("typescript", "~5.4.0", &[]),
+    ];
+    let mut selected = vec![update("typescript", "5.5.0", &[])];
+
+    let check = check_peer_dependencies(&mut selected, &[], &dependents);
+
+    assert!(check.added.is_empty());
+    assert!(check.conflicts.is_empty());
+  }
+}
@@ -1,10 +1,47 @@
-use inquire::{formatter::MultiOptionFormatter, validator::MinLengthValidator, MultiSelect};
+use colored::Colorize;
+use inquire::{
+  formatter::MultiOptionFormatter, validator::MinLengthValidator, Confirm, MultiSelect,
+};
+use std::fmt::Display;
+use std::ops::Range;
 
-use super::package_info::PackageInfo;
+use super::{
+  package_info::{diff_group_title, PackageInfo},
+  versions::SemverDiff,
+};
 
+/// A prompt row: the title of a semver group, selecting all of its packages, or a single package.
+enum UpdateOption<'a> {
+  Group(Option<SemverDiff>, Range<usize>),
+  Package(usize, &'a PackageInfo),
+}
+
+impl Display for UpdateOption<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      UpdateOption::Group(diff, indexes) => write!(
+        f,
+        "{} {}",
+        diff_group_title(*diff),
+        format!("({}, select to update all)", indexes.len()).dimmed()
+      ),
+      UpdateOption::Package(_, package) => write!(f, "  {package}"),
+    }
+  }
+}
+
+/// Prompt the packages to update, grouped by semver difference.
+///
+/// Packages are expected to be sorted by semver difference already.
 pub fn display_update(updatable_packages: Vec<PackageInfo>) -> Option<Vec<PackageInfo>> {
-  let formatter: MultiOptionFormatter<'_, PackageInfo> =
-    &|a| format!("{} package(s) selected", a.len());
+  let total = updatable_packages.len();
+  let formatter: MultiOptionFormatter<'_, UpdateOption> = &|selected| {
+    let count = selected_packages(selected.iter().map(|option| option.value), total)
+      .into_iter()
+      .filter(|is_selected| *is_selected)
+      .count();
+    format!("{count} package(s) selected")
+  };
 
   let validator = MinLengthValidator::new(1).with_message("Please select at least one package.");
 
@@ -13,13 +50,75 @@ pub fn display_update(updatable_packages: Vec<PackageInfo>) -> Option<Vec<Packag
     updatable_packages.len()
   );
 
-  let prompt = MultiSelect::new(&prompt_message, updatable_packages)
+  let mut options = Vec::new();
+  let mut group_start = 0;
+  for group in updatable_packages.chunk_by(|a, b| a.diff == b.diff) {
+    let indexes = group_start..group_start + group.len();
+    options.push(UpdateOption::Group(group[0].diff, indexes.clone()));
+    options.extend(
+      indexes
+        .zip(group)
+        .map(|(index, package)| UpdateOption::Package(index, package)),
+    );
+    group_start += group.len();
+  }
+
+  let selected = MultiSelect::new(&prompt_message, options)
     .with_formatter(formatter)
     .with_validator(validator)
-    .prompt();
+    .prompt()
+    .ok()?;
+
+  let is_selected = selected_packages(&selected, total);
+
+  Some(
+    updatable_packages
+      .into_iter()
+      .zip(is_selected)
+      .filter_map(|(package, is_selected)| is_selected.then_some(package))
+      .collect(),
+  )
+}
+
+/// Whether each package is selected, on its own or through its group.
+fn selected_packages<'a, 'p: 'a>(
+  options: impl IntoIterator<Item = &'a UpdateOption<'p>>,
+  total: usize,
+) -> Vec<bool> {
+  let mut is_selected = vec![false; total];
+  for option in options {
+    match option {
+      UpdateOption::Group(_, indexes) => is_selected[indexes.clone()].fill(true),
+      UpdateOption::Package(index, _) => is_selected[*index] = true,
+    }
+  }
+  is_selected
+}
+
+/// Ask whether to install updates breaking some peer dependencies.
+pub fn confirm_peer_conflicts() -> bool {
+  Confirm::new("Install anyway?")
+    .with_default(false)
+    .prompt()
+    .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_selected_packages() {
+    let package = PackageInfo::default();
+    let options = [
+      UpdateOption::Group(Some(SemverDiff::Major), 0..3),
+      UpdateOption::Package(1, &package),
+      UpdateOption::Package(4, &package),
+    ];
 
-  match prompt {
-    Ok(selected) => Some(selected),
-    Err(_) => None,
+    assert_eq!(
+      selected_packages(&options, 5),
+      [true, true, true, false, true]
+    );
   }
 }
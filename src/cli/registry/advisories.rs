@@ -0,0 +1,54 @@
+use nodejs_semver::{Range, Version};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// Path of the bulk advisory endpoint, relative to the registry URL.
+pub const ADVISORIES_BULK_PATH: &str = "-/npm/v1/security/advisories/bulk";
+
+/// Body of a bulk advisory request: the versions to audit of every package.
+pub type AuditRequest = HashMap<String, Vec<String>>;
+
+/// A security advisory, as returned by the bulk advisory endpoint.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct Advisory {
+  pub id: u64,
+  pub title: String,
+  pub url: String,
+  pub severity: Severity,
+  /// Range of the affected versions: `<4.17.21`.
+  pub vulnerable_versions: String,
+}
+
+impl Advisory {
+  pub fn range(&self) -> Option<Range> {
+    Range::parse(&self.vulnerable_versions).ok()
+  }
+
+  pub fn affects(&self, version: &Version) -> bool {
+    self.range().is_some_and(|range| range.satisfies(version))
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+  Info,
+  Low,
+  Moderate,
+  High,
+  Critical,
+}
+
+impl Display for Severity {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Severity::Info => write!(f, "info"),
+      Severity::Low => write!(f, "low"),
+      Severity::Moderate => write!(f, "moderate"),
+      Severity::High => write!(f, "high"),
+      Severity::Critical => write!(f, "critical"),
+    }
+  }
+}
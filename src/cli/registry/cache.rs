@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Environment variable overriding the directory of the metadata cache.
+const CACHE_DIR_ENV: &str = "PUSHAPP_CACHE_DIR";
+
+/// How the registry metadata cache answers the lookups.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+  /// Revalidate the cached metadata with conditional requests.
+  #[default]
+  Revalidate,
+  /// Use the cached metadata as is, only fetching the packages missing from the cache.
+  PreferOffline,
+  /// Never hit the network, the packages missing from the cache fail.
+  Offline,
+}
+
+impl CacheMode {
+  pub fn uses_network(self) -> bool {
+    self != CacheMode::Offline
+  }
+}
+
+/// A cached packument, with the validators of the response it came from.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedMetadata {
+  pub etag: Option<String>,
+  pub last_modified: Option<String>,
+  /// Raw packument, parsed on each read.
+  pub body: String,
+}
+
+/// Registry metadata stored on disk, one file per registry, package and document kind.
+#[derive(Debug, Clone)]
+pub struct MetadataCache {
+  dir: PathBuf,
+}
+
+impl MetadataCache {
+  pub fn new(dir: PathBuf) -> Self {
+    Self { dir }
+  }
+
+  /// The cache under `$PUSHAPP_CACHE_DIR`, or the user cache directory: `~/.cache/pushapp`.
+  pub fn from_env() -> Option<Self> {
+    std::env::var_os(CACHE_DIR_ENV)
+      .map(PathBuf::from)
+      .or_else(|| dirs::cache_dir().map(|dir| dir.join("pushapp")))
+      .map(Self::new)
+  }
+
+  pub fn read(&self, registry_url: &str, name: &str, full: bool) -> Option<CachedMetadata> {
+    let content = fs::read_to_string(self.path_of(registry_url, name, full)).ok()?;
+    serde_json::from_str(&content).ok()
+  }
+
+  /// Store the metadata of a package, a failure only costing a download on the next run.
+  pub fn write(&self, registry_url: &str, name: &str, full: bool, metadata: &CachedMetadata) {
+    let path = self.path_of(registry_url, name, full);
+    let Ok(content) = serde_json::to_string(metadata) else {
+      return;
+    };
+
+    // Write then rename, so that concurrent runs never read a partial file
+    let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    let written = path
+      .parent()
+      .is_some_and(|dir| fs::create_dir_all(dir).is_ok())
+      && fs::write(&temp_path, content).is_ok()
+      && fs::rename(&temp_path, &path).is_ok();
+    if !written {
+      let _ = fs::remove_file(temp_path);
+    }
+  }
+
+  /// `<dir>/registry.npmjs.org/@scope%2fname.full.json`
+  fn path_of(&self, registry_url: &str, name: &str, full: bool) -> PathBuf {
+    let registry = registry_url
+      .split_once("://")
+      .map_or(registry_url, |(_, rest)| rest)
+      .trim_end_matches('/');
+    let registry: String = registry
+      .chars()
+      .map(|c| {
+        if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') {
+          c
+        } else {
+          '_'
+        }
+      })
+      .collect();
+    let kind = if full { "full" } else { "abbreviated" };
+
+    self
+      .dir
+      .join(registry)
+      .join(format!("{}.{kind}.json", name.replace('/', "%2f")))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::tempdir;
+
+  #[test]
+  fn test_read_written_metadata() {
+    let dir = tempdir().unwrap();
+    let cache = MetadataCache::new(dir.path().to_path_buf());
+    let metadata = CachedMetadata {
+      etag: Some("\"abc\"".to_string()),
+      body: "{}".to_string(),
+      ..Default::default()
+    };
+
+    cache.write(
+      "https://registry.npmjs.org/",
+      "@types/node",
+      true,
+      &metadata,
+    );
+
+    assert!(dir
+      .path()
+      .join("registry.npmjs.org/@types%2fnode.full.json")
+      .exists());
+    let full = cache.read("https://registry.npmjs.org/", "@types/node", true);
+    assert_eq!(full.unwrap().etag.as_deref(), Some("\"abc\""));
+    assert!(cache
+      .read("https://registry.npmjs.org/", "@types/node", false)
+      .is_none());
+    assert!(cache
+      .read("https://npm.company.com/", "@types/node", true)
+      .is_none());
+  }
+}
@@ -1,89 +1,671 @@
-use anyhow::Result;
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT};
-use reqwest::{Client, ClientBuilder};
+use nodejs_semver::Version;
+use reqwest::header::{
+  HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+  LAST_MODIFIED, RETRY_AFTER,
+};
+use reqwest::{Client, ClientBuilder, RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::time::Duration;
 use url::Url;
 
-use super::{RegistryClientOptions, RegistryError};
+use super::{
+  fetch_git_tags, Advisory, AuditRequest, CacheMode, CachedMetadata, MetadataCache, NpmConfig,
+  RegistryAuth, RegistryClientOptions, RegistryError, ADVISORIES_BULK_PATH,
+};
 
 use crate::cli::{
-  flags::Flags,
   package_info::PackageInfo,
-  versions::{is_version_satisfying, match_dist_tag_with_target, DistTags},
+  versions::{current_version_of, find_target_version, Packument, SemverDiff, UpdateRule},
 };
 
+/// Ask for the abbreviated packument, falling back to the full document on registries lacking it.
+const ABBREVIATED_METADATA: &str =
+  "application/vnd.npm.install-v1+json; q=1.0, application/json; q=0.8, */*";
+
+/// The full packument, needed for the publish times.
+const FULL_METADATA: &str = "application/json";
+
+/// Outcome of a lookup: the update, if any, and the peer dependencies and deprecation message of
+/// the current version.
+#[derive(Debug, Default, Clone)]
+pub struct PackageLookup {
+  pub update: Option<PackageInfo>,
+  pub peer_dependencies: HashMap<String, String>,
+  pub deprecated: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct RegistryClient {
   pub client: Client,
-  pub registry_url: String,
+  pub config: NpmConfig,
+  /// Always fetch the full packument, for the publish times.
+  pub full_metadata: bool,
+  /// On-disk metadata cache, none to always download the packuments.
+  pub cache: Option<MetadataCache>,
+  pub cache_mode: CacheMode,
+  pub options: RegistryClientOptions,
 }
 
 impl Default for RegistryClient {
   fn default() -> Self {
-    let registry_options = RegistryClientOptions::default();
+    Self::new(NpmConfig::default())
+  }
+}
+
+impl RegistryClient {
+  pub fn new(config: NpmConfig) -> Self {
+    let defaults = RegistryClientOptions::default();
+    let registry_options = RegistryClientOptions {
+      max_sockets: config.max_sockets().unwrap_or(defaults.max_sockets),
+      strict_ssl: config.strict_ssl().unwrap_or(defaults.strict_ssl),
+      retries: config.fetch_retries().unwrap_or(defaults.retries),
+      retry_min_timeout: config
+        .fetch_retry_min_timeout()
+        .unwrap_or(defaults.retry_min_timeout),
+      retry_max_timeout: config
+        .fetch_retry_max_timeout()
+        .unwrap_or(defaults.retry_max_timeout),
+      ..defaults
+    };
 
     let client = ClientBuilder::new()
       .pool_max_idle_per_host(registry_options.max_sockets)
-      .timeout(std::time::Duration::from_secs(registry_options.timeout))
+      .timeout(Duration::from_millis(registry_options.timeout))
       .danger_accept_invalid_certs(!registry_options.strict_ssl)
       .build()
       .unwrap();
 
     Self {
       client,
-      registry_url: "https://registry.npmjs.org".to_string(),
+      config,
+      full_metadata: false,
+      cache: None,
+      cache_mode: CacheMode::default(),
+      options: registry_options,
     }
   }
-}
 
-impl RegistryClient {
-  pub async fn get_package_info(
+  /// Fetch the versions of a registry package, along with their publish times when
+  /// `publish_times` is set or the client always fetches the full metadata.
+  pub async fn get_packument(
     &self,
+    name: &str,
+    publish_times: bool,
+  ) -> Result<Packument, RegistryError> {
+    self
+      .fetch_registry(name, self.full_metadata || publish_times)
+      .await
+  }
+
+  /// The semver tags of a git dependency, as the versions of a packument.
+  pub async fn get_git_packument(&self, name: &str, url: &str) -> Result<Packument, RegistryError> {
+    if !self.cache_mode.uses_network() {
+      return Err(RegistryError::NotCached(name.to_string()));
+    }
+
+    let tags = fetch_git_tags(url).await?;
+    Ok(Packument::from_versions(
+      tags.iter().filter_map(|tag| Version::parse(tag).ok()),
+    ))
+  }
+
+  /// The update of a dependency out of the versions of its package.
+  pub fn find_update(
     name: &str,
     current_version: &str,
-    flags: &Flags,
-  ) -> Result<Option<PackageInfo>> {
-    let latest_version = self.fetch_package_version(name, flags).await?;
-
-    match latest_version {
-      Some(version) if is_version_satisfying(current_version, &version, flags)? => {
-        Ok(Some(PackageInfo {
-          pkg_name: name.to_string(),
-          current_version: current_version.to_string(),
-          latest_version: version,
-        }))
+    packument: &Packument,
+    rule: &UpdateRule,
+  ) -> Result<PackageLookup, RegistryError> {
+    let target_version = find_target_version(current_version, packument, rule).map_err(|e| {
+      RegistryError::InvalidVersion(name.to_string(), current_version.to_string(), e.to_string())
+    })?;
+    let current = current_version_of(current_version)
+      .ok()
+      .map(|current| current.to_string());
+
+    let update = target_version.map(|version| PackageInfo {
+      pkg_name: name.to_string(),
+      current_version: current_version.to_string(),
+      diff: SemverDiff::between(current_version, &version),
+      current_published_at: current
+        .as_ref()
+        .and_then(|current| packument.published_at(current)),
+      published_at: packument.published_at(&version),
+      repository: packument.repository_url(),
+      tarball: packument.tarball_of(&version),
+      peer_dependencies: packument.peer_dependencies_of(&version),
+      current_deprecated: current
+        .as_ref()
+        .and_then(|current| packument.deprecation_of(current)),
+      deprecated: packument.deprecation_of(&version),
+      advisories: Vec::new(),
+      latest_version: version,
+      dep_type: None,
+      workspace: None,
+      manager: None,
+    });
+
+    Ok(PackageLookup {
+      update,
+      peer_dependencies: current
+        .as_ref()
+        .map(|current| packument.peer_dependencies_of(current))
+        .unwrap_or_default(),
+      deprecated: current.and_then(|current| packument.deprecation_of(&current)),
+    })
+  }
+
+  async fn fetch_registry(&self, name: &str, full: bool) -> Result<Packument, RegistryError> {
+    // Scoped packages keep their `@` but need the slash escaped: `@scope%2fname`
+    let registry_url = self.config.registry_for(name);
+    let full_url = format!("{}{}", registry_url, name.replace('/', "%2f"));
+    let package_url = Url::parse(&full_url)?;
+
+    let cached = match (self.cache_mode, self.read_cache(&registry_url, name, full)) {
+      (CacheMode::Offline | CacheMode::PreferOffline, Some((_, cached))) => {
+        return parse_packument(name, &cached.body);
+      }
+      (CacheMode::Offline, None) => return Err(RegistryError::NotCached(name.to_string())),
+      // Only revalidate the document of the requested kind
+      (_, cached) => {
+        cached.and_then(|(cached_full, cached)| (cached_full == full).then_some(cached))
+      }
+    };
+
+    let mut headers = HeaderMap::new();
+    let accept = if full {
+      FULL_METADATA
+    } else {
+      ABBREVIATED_METADATA
+    };
+    headers.insert(ACCEPT, HeaderValue::from_static(accept));
+    if let Some(cached) = &cached {
+      let validators = [
+        (IF_NONE_MATCH, &cached.etag),
+        (IF_MODIFIED_SINCE, &cached.last_modified),
+      ];
+      for (header, value) in validators {
+        if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+          headers.insert(header, value);
+        }
       }
-      _ => Ok(None),
     }
+
+    let request = self.client.get(package_url).headers(headers);
+    let response = self
+      .send(name, self.authorize(request, &registry_url))
+      .await?;
+
+    if let (StatusCode::NOT_MODIFIED, Some(cached)) = (response.status(), &cached) {
+      return parse_packument(name, &cached.body);
+    }
+
+    let header = |name| {
+      response
+        .headers()
+        .get(name)
+        .and_then(|value: &HeaderValue| value.to_str().ok())
+        .map(str::to_string)
+    };
+    let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+    let body = response.text().await?;
+    let packument = parse_packument(name, &body)?;
+
+    if let Some(cache) = &self.cache {
+      let metadata = CachedMetadata {
+        etag,
+        last_modified,
+        body,
+      };
+      cache.write(&registry_url, name, full, &metadata);
+    }
+
+    Ok(packument)
   }
 
-  async fn fetch_package_version(
+  /// Cached metadata of a package and whether it is the full packument, which also answers for
+  /// the abbreviated one when the cache isn't revalidated.
+  fn read_cache(
     &self,
+    registry_url: &str,
     name: &str,
-    flags: &Flags,
-  ) -> Result<Option<String>, RegistryError> {
-    let dist_tags = self.fetch_registry(name).await?;
-    let version_match = match_dist_tag_with_target(dist_tags, &flags.target);
+    full: bool,
+  ) -> Option<(bool, CachedMetadata)> {
+    let cache = self.cache.as_ref()?;
+    let exact = cache
+      .read(registry_url, name, full)
+      .map(|cached| (full, cached));
 
-    Ok(version_match)
+    match self.cache_mode {
+      CacheMode::Revalidate => exact,
+      _ if full => exact,
+      _ => exact.or_else(|| {
+        cache
+          .read(registry_url, name, true)
+          .map(|cached| (true, cached))
+      }),
+    }
   }
 
-  async fn fetch_registry(&self, name: &str) -> Result<DistTags, RegistryError> {
-    let full_url = format!("{}/-/package/{}/dist-tags", self.registry_url, name);
-    let package_url = Url::parse(&full_url)?;
+  /// Advisories affecting some of the given versions, each registry being asked about the
+  /// packages it serves.
+  pub async fn fetch_advisories(
+    &self,
+    versions: &AuditRequest,
+  ) -> Result<HashMap<String, Vec<Advisory>>, RegistryError> {
+    let mut requests: HashMap<String, AuditRequest> = HashMap::new();
+    for (name, package_versions) in versions {
+      requests
+        .entry(self.config.registry_for(name))
+        .or_default()
+        .insert(name.clone(), package_versions.clone());
+    }
 
-    let mut headers = HeaderMap::new();
-    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    let mut advisories = HashMap::new();
+    for (registry_url, body) in requests {
+      let url = Url::parse(&format!("{registry_url}{ADVISORIES_BULK_PATH}"))?;
+      let request = self.client.post(url).json(&body);
+      let response = self.authorize(request, &registry_url).send().await?;
+
+      if !response.status().is_success() {
+        return Err(RegistryError::AdvisoriesUnavailable(
+          registry_url,
+          response.status(),
+        ));
+      }
+      advisories.extend(response.json::<HashMap<String, Vec<Advisory>>>().await?);
+    }
 
+    Ok(advisories)
+  }
+
+  /// Download a package tarball, with the credentials of the registry serving it.
+  pub async fn fetch_tarball(
+    &self,
+    name: &str,
+    tarball_url: &str,
+  ) -> Result<Vec<u8>, RegistryError> {
+    let request = self.client.get(Url::parse(tarball_url)?);
     let response = self
-      .client
-      .get(package_url)
-      .headers(headers)
-      .send()
-      .await?
-      .json::<DistTags>()
+      .send(name, self.authorize(request, tarball_url))
+      .await?;
+
+    Ok(response.bytes().await?.to_vec())
+  }
+
+  fn authorize(&self, request: RequestBuilder, registry_url: &str) -> RequestBuilder {
+    match self.config.auth_for(registry_url) {
+      Some(RegistryAuth::Token(token)) => request.bearer_auth(token),
+      Some(RegistryAuth::Basic(credentials)) => {
+        request.header(AUTHORIZATION, format!("Basic {credentials}"))
+      }
+      None => request,
+    }
+  }
+
+  /// Send a request, retrying the transient failures with a jittered exponential backoff.
+  async fn send(&self, name: &str, request: RequestBuilder) -> Result<Response, RegistryError> {
+    let mut attempt = 0;
+    loop {
+      // Only requests with a streamed body can't be cloned, the GETs always can
+      let Some(current) = request.try_clone() else {
+        return check_response(name, request.send().await);
+      };
+
+      let response = current.send().await;
+      let retry_after = response
+        .as_ref()
+        .ok()
+        .and_then(|response| retry_after(response.headers()));
+
+      let result = check_response(name, response);
+      let delay = match &result {
+        Err(e) if e.is_transient() && attempt < self.options.retries => {
+          self.retry_delay(attempt, retry_after)
+        }
+        _ => None,
+      };
+      let Some(delay) = delay else {
+        return result;
+      };
+
+      tokio::time::sleep(delay).await;
+      attempt += 1;
+    }
+  }
+
+  /// Delay before retrying: the one asked by the registry, or a growing one spread between its
+  /// half and itself so that the concurrent lookups don't retry all at once. `None` when the
+  /// registry asks to wait longer than the maximum delay, the request then failing as is.
+  fn retry_delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+    if let Some(retry_after) = retry_after {
+      let max_delay = Duration::from_millis(self.options.retry_max_timeout);
+      return (retry_after <= max_delay).then_some(retry_after);
+    }
+
+    let backoff = self
+      .options
+      .retry_min_timeout
+      .saturating_mul(1 << attempt.min(16))
+      .min(self.options.retry_max_timeout);
+    let jitter = RandomState::new().build_hasher().finish() % (backoff / 2 + 1);
+    Some(Duration::from_millis(backoff - backoff / 2 + jitter))
+  }
+}
+
+fn check_response(
+  name: &str,
+  response: reqwest::Result<Response>,
+) -> Result<Response, RegistryError> {
+  let response = response.map_err(|e| {
+    if e.is_timeout() {
+      RegistryError::Timeout(name.to_string())
+    } else {
+      RegistryError::RequestError(e)
+    }
+  })?;
+
+  match response.status() {
+    // Answer to a conditional request, the caller holds the cached document
+    status if status.is_success() || status == StatusCode::NOT_MODIFIED => Ok(response),
+    StatusCode::NOT_FOUND => Err(RegistryError::PackageNotFound(name.to_string())),
+    status @ (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+      Err(RegistryError::AuthDenied(name.to_string(), status))
+    }
+    StatusCode::REQUEST_TIMEOUT => Err(RegistryError::Timeout(name.to_string())),
+    StatusCode::TOO_MANY_REQUESTS => Err(RegistryError::RateLimited(name.to_string())),
+    status if status.is_server_error() => Err(RegistryError::Unavailable(name.to_string(), status)),
+    status => Err(RegistryError::UnexpectedStatus(name.to_string(), status)),
+  }
+}
+
+/// The `Retry-After` header, either a number of seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+  let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+  if let Ok(seconds) = value.parse::<u64>() {
+    return Some(Duration::from_secs(seconds));
+  }
+
+  let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+  (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+    .to_std()
+    .ok()
+    .or(Some(Duration::ZERO))
+}
+
+fn parse_packument(name: &str, body: &str) -> Result<Packument, RegistryError> {
+  serde_json::from_str(body).map_err(|e| RegistryError::InvalidMetadata(name.to_string(), e))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cli::registry::Severity;
+  use std::collections::HashMap;
+
+  #[tokio::test]
+  async fn test_fetch_registry_with_scoped_registry_auth() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+      .mock("GET", "/@company%2fui")
+      .match_header("authorization", "Bearer secret")
+      .with_body(r#"{ "dist-tags": { "latest": "2.0.0" }, "versions": {} }"#)
+      .create_async()
+      .await;
+
+    let registry = server.url().replace("http:", "");
+    let client = RegistryClient::new(NpmConfig::from_values(HashMap::from([
+      ("@company:registry".to_owned(), server.url()),
+      (format!("{registry}/:_authToken"), "secret".to_owned()),
+    ])));
+
+    let packument = client.fetch_registry("@company/ui", false).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(packument.dist_tags.latest, "2.0.0");
+  }
+
+  #[tokio::test]
+  async fn test_fetch_registry_errors() {
+    let mut server = mockito::Server::new_async().await;
+    server
+      .mock("GET", "/missing")
+      .with_status(404)
+      .create_async()
+      .await;
+    server
+      .mock("GET", "/private")
+      .with_status(401)
+      .create_async()
+      .await;
+
+    let client = RegistryClient::new(NpmConfig::from_values(HashMap::from([(
+      "registry".to_owned(),
+      server.url(),
+    )])));
+
+    assert!(matches!(
+      client.fetch_registry("missing", false).await,
+      Err(RegistryError::PackageNotFound(_))
+    ));
+    assert!(matches!(
+      client.fetch_registry("private", false).await,
+      Err(RegistryError::AuthDenied(_, StatusCode::UNAUTHORIZED))
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_fetch_advisories() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+      .mock("POST", "/-/npm/v1/security/advisories/bulk")
+      .match_body(mockito::Matcher::Json(
+        serde_json::json!({ "lodash": ["4.17.20"] }),
+      ))
+      .with_body(
+        r#"{ "lodash": [{
+          "id": 1106913,
+          "url": "https://github.com/advisories/GHSA-35jh-r3h4-6jhm",
+          "title": "Command Injection in lodash",
+          "severity": "high",
+          "vulnerable_versions": "<4.17.21",
+          "cwe": ["CWE-77"]
+        }] }"#,
+      )
+      .create_async()
+      .await;
+
+    let client = RegistryClient::new(NpmConfig::from_values(HashMap::from([(
+      "registry".to_owned(),
+      server.url(),
+    )])));
+
+    let advisories = client
+      .fetch_advisories(&HashMap::from([(
+        "lodash".to_owned(),
+        vec!["4.17.20".to_owned()],
+      )]))
       .await
-      .map_err(|e| RegistryError::PackageNotFound(name.to_string(), e))?;
+      .unwrap();
+
+    mock.assert_async().await;
+    let advisory = &advisories["lodash"][0];
+    assert_eq!(advisory.severity, Severity::High);
+    assert!(advisory.affects(&Version::parse("4.17.20").unwrap()));
+    assert!(!advisory.affects(&Version::parse("4.17.21").unwrap()));
+  }
+
+  #[tokio::test]
+  async fn test_find_update_with_min_age() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+      .mock("GET", "/react")
+      .match_header("accept", FULL_METADATA)
+      .with_body(
+        r#"{
+          "dist-tags": { "latest": "1.1.0" },
+          "versions": { "1.0.0": { "version": "1.0.0" }, "1.1.0": { "version": "1.1.0" } },
+          "time": { "1.0.0": "2024-01-01T00:00:00.000Z", "1.1.0": "2099-01-01T00:00:00.000Z" }
+        }"#,
+      )
+      .create_async()
+      .await;
+
+    let client = RegistryClient::new(NpmConfig::from_values(HashMap::from([(
+      "registry".to_owned(),
+      server.url(),
+    )])));
+    let rule = UpdateRule {
+      published_before: Some(chrono::Utc::now()),
+      ..Default::default()
+    };
+
+    let packument = client.get_packument("react", true).await.unwrap();
+    let info = RegistryClient::find_update("react", "0.9.0", &packument, &rule)
+      .unwrap()
+      .update
+      .unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(info.latest_version, "1.0.0");
+    assert!(info.published_at.is_some());
+  }
+
+  #[tokio::test]
+  async fn test_fetch_registry_with_cache() {
+    let mut server = mockito::Server::new_async().await;
+    let fetch = server
+      .mock("GET", "/react")
+      .match_header("if-none-match", mockito::Matcher::Missing)
+      .with_header("etag", "\"v1\"")
+      .with_body(r#"{ "dist-tags": { "latest": "1.0.0" }, "versions": {} }"#)
+      .create_async()
+      .await;
+    let revalidate = server
+      .mock("GET", "/react")
+      .match_header("if-none-match", "\"v1\"")
+      .with_status(304)
+      .create_async()
+      .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut client = RegistryClient::new(NpmConfig::from_values(HashMap::from([(
+      "registry".to_owned(),
+      server.url(),
+    )])));
+    client.cache = Some(MetadataCache::new(dir.path().to_path_buf()));
+
+    let fetched = client.fetch_registry("react", false).await.unwrap();
+    let revalidated = client.fetch_registry("react", false).await.unwrap();
+    client.cache_mode = CacheMode::Offline;
+    let offline = client.fetch_registry("react", false).await.unwrap();
+    let missing = client.fetch_registry("vue", false).await;
+
+    fetch.assert_async().await;
+    revalidate.assert_async().await;
+    assert_eq!(fetched.dist_tags.latest, "1.0.0");
+    assert_eq!(revalidated.dist_tags.latest, "1.0.0");
+    assert_eq!(offline.dist_tags.latest, "1.0.0");
+    assert!(matches!(missing, Err(RegistryError::NotCached(_))));
+  }
+
+  #[tokio::test]
+  async fn test_send_retries_transient_failures() {
+    let mut server = mockito::Server::new_async().await;
+    let unavailable = server
+      .mock("GET", "/react")
+      .with_status(503)
+      .expect(1)
+      .create_async()
+      .await;
+    let rate_limited = server
+      .mock("GET", "/react")
+      .with_status(429)
+      .with_header("retry-after", "0")
+      .expect(1)
+      .create_async()
+      .await;
+    let available = server
+      .mock("GET", "/react")
+      .with_body(r#"{ "dist-tags": { "latest": "1.0.0" }, "versions": {} }"#)
+      .create_async()
+      .await;
+    let missing = server
+      .mock("GET", "/missing")
+      .with_status(404)
+      .expect(1)
+      .create_async()
+      .await;
+
+    let mut client = RegistryClient::new(NpmConfig::from_values(HashMap::from([(
+      "registry".to_owned(),
+      server.url(),
+    )])));
+    client.options.retry_min_timeout = 1;
+
+    let packument = client.fetch_registry("react", false).await.unwrap();
+    let not_found = client.fetch_registry("missing", false).await;
+
+    unavailable.assert_async().await;
+    rate_limited.assert_async().await;
+    available.assert_async().await;
+    missing.assert_async().await;
+    assert_eq!(packument.dist_tags.latest, "1.0.0");
+    assert!(matches!(not_found, Err(RegistryError::PackageNotFound(_))));
+  }
+
+  #[tokio::test]
+  async fn test_send_gives_up_after_retries() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+      .mock("GET", "/react")
+      .with_status(502)
+      .expect(3)
+      .create_async()
+      .await;
+
+    let mut client = RegistryClient::new(NpmConfig::from_values(HashMap::from([(
+      "registry".to_owned(),
+      server.url(),
+    )])));
+    client.options.retry_min_timeout = 1;
+
+    let result = client.fetch_registry("react", false).await;
+
+    mock.assert_async().await;
+    assert!(matches!(
+      result,
+      Err(RegistryError::Unavailable(_, StatusCode::BAD_GATEWAY))
+    ));
+  }
+
+  #[test]
+  fn test_retry_after() {
+    let headers = |value: &str| HeaderMap::from_iter([(RETRY_AFTER, value.parse().unwrap())]);
+
+    assert_eq!(retry_after(&headers("120")), Some(Duration::from_secs(120)));
+    assert_eq!(
+      retry_after(&headers("Wed, 21 Oct 2015 07:28:00 GMT")),
+      Some(Duration::ZERO)
+    );
+    assert_eq!(retry_after(&headers("soon")), None);
+    assert_eq!(retry_after(&HeaderMap::new()), None);
+  }
+
+  #[test]
+  fn test_retry_delay() {
+    let client = RegistryClient::default();
+
+    let first = client.retry_delay(0, None).unwrap();
+    let third = client.retry_delay(2, None).unwrap();
 
-    Ok(response)
+    assert!(first >= Duration::from_millis(500) && first <= Duration::from_secs(1));
+    assert!(third >= Duration::from_secs(2) && third <= Duration::from_secs(4));
+    assert_eq!(
+      client.retry_delay(0, Some(Duration::from_secs(30))),
+      Some(Duration::from_secs(30))
+    );
+    assert_eq!(client.retry_delay(0, Some(Duration::from_secs(3600))), None);
   }
 }
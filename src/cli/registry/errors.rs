@@ -1,11 +1,64 @@
+use reqwest::StatusCode;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum RegistryError {
-  #[error("Package {0} could not be found: {1}")]
-  PackageNotFound(String, reqwest::Error),
+  #[error("Package {0} could not be found")]
+  PackageNotFound(String),
+  #[error("Access to package {0} was denied by the registry ({1})")]
+  AuthDenied(String, StatusCode),
+  #[error("Request for package {0} timed out")]
+  Timeout(String),
+  #[error("Registry is unavailable for package {0} ({1}), even after retrying")]
+  Unavailable(String, StatusCode),
+  #[error("Registry rate limit hit for package {0}, even after retrying")]
+  RateLimited(String),
+  #[error("Registry answered {1} for package {0}")]
+  UnexpectedStatus(String, StatusCode),
+  #[error("Invalid registry metadata for package {0}: {1}")]
+  InvalidMetadata(String, serde_json::Error),
+  #[error("Version {1} of package {0} could not be parsed: {2}")]
+  InvalidVersion(String, String, String),
+  #[error("Registry {0} answered {1} for the security advisories")]
+  AdvisoriesUnavailable(String, StatusCode),
+  #[error("Package {0} isn't in the cache, it can't be checked offline")]
+  NotCached(String),
+  #[error("Couldn't list the tags of {0}: {1}")]
+  GitError(String, String),
   #[error("HTTP request error: {0}")]
   RequestError(#[from] reqwest::Error),
   #[error("URL parse error: {0}")]
   ParseError(#[from] url::ParseError),
 }
+
+impl RegistryError {
+  /// Whether the failure may not happen again, so that the request is worth retrying.
+  pub fn is_transient(&self) -> bool {
+    match self {
+      RegistryError::Timeout(..)
+      | RegistryError::Unavailable(..)
+      | RegistryError::RateLimited(..) => true,
+      RegistryError::RequestError(e) => e.is_connect() || e.is_timeout(),
+      _ => false,
+    }
+  }
+
+  /// Short description of the failure, used to summarize the failed lookups.
+  pub fn kind(&self) -> &'static str {
+    match self {
+      RegistryError::PackageNotFound(..) => "not found",
+      RegistryError::AuthDenied(..) => "auth denied",
+      RegistryError::Timeout(..) => "timeout",
+      RegistryError::Unavailable(..) => "registry unavailable",
+      RegistryError::RateLimited(..) => "rate limited",
+      RegistryError::InvalidVersion(..) => "unparsable version",
+      RegistryError::GitError(..) => "git error",
+      RegistryError::NotCached(..) => "not cached",
+      RegistryError::UnexpectedStatus(..)
+      | RegistryError::AdvisoriesUnavailable(..)
+      | RegistryError::InvalidMetadata(..)
+      | RegistryError::RequestError(..)
+      | RegistryError::ParseError(..) => "request error",
+    }
+  }
+}
@@ -0,0 +1,92 @@
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::RegistryError;
+
+/// Longest wait for a repository to list its tags.
+const LS_REMOTE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// List the tags of a git repository with `git ls-remote`, without cloning it.
+///
+/// The URL comes from `package.json`: it can't be read as an option, nor run a command through the
+/// `ext::` transport. Git never prompts for credentials, a private repository failing instead of
+/// blocking the run.
+pub async fn fetch_git_tags(url: &str) -> Result<Vec<String>, RegistryError> {
+  if url.starts_with('-') {
+    return Err(RegistryError::GitError(
+      url.to_string(),
+      "not a repository URL".to_string(),
+    ));
+  }
+
+  let mut command = Command::new("git");
+  command
+    .args([
+      "-c",
+      "protocol.ext.allow=never",
+      "ls-remote",
+      "--tags",
+      "--refs",
+      "--",
+      url,
+    ])
+    .env("GIT_TERMINAL_PROMPT", "0")
+    .env("GIT_SSH_COMMAND", "ssh -oBatchMode=yes")
+    .stdin(Stdio::null())
+    .kill_on_drop(true);
+
+  let output = tokio::time::timeout(LS_REMOTE_TIMEOUT, command.output())
+    .await
+    .map_err(|_| {
+      RegistryError::GitError(
+        url.to_string(),
+        format!("timed out after {}s", LS_REMOTE_TIMEOUT.as_secs()),
+      )
+    })?
+    .map_err(|e| RegistryError::GitError(url.to_string(), e.to_string()))?;
+
+  if !output.status.success() {
+    return Err(RegistryError::GitError(
+      url.to_string(),
+      String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    ));
+  }
+
+  Ok(parse_ls_remote(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Extract tag names from `<sha>\trefs/tags/<name>` lines.
+fn parse_ls_remote(output: &str) -> Vec<String> {
+  output
+    .lines()
+    .filter_map(|line| line.split_once("refs/tags/"))
+    .map(|(_, tag)| tag.trim().to_string())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_ls_remote() {
+    let output = "a1b2c3\trefs/tags/v1.0.0\nd4e5f6\trefs/tags/v1.1.0\n";
+    assert_eq!(parse_ls_remote(output), ["v1.0.0", "v1.1.0"]);
+  }
+
+  #[tokio::test]
+  async fn test_fetch_git_tags_rejects_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("pwn");
+    let option_url = format!("--upload-pack=touch {};git-upload-pack", marker.display());
+    let ext_url = format!("ext::sh -c touch% {}", marker.display());
+
+    assert!(matches!(
+      fetch_git_tags(&option_url).await,
+      Err(RegistryError::GitError(..))
+    ));
+    assert!(fetch_git_tags(&ext_url).await.is_err());
+    assert!(!marker.exists());
+  }
+}
@@ -1,7 +1,15 @@
+pub mod advisories;
+pub mod cache;
 pub mod client;
 pub mod errors;
+pub mod git;
+pub mod npmrc;
 pub mod options;
 
+pub(super) use advisories::*;
+pub(super) use cache::*;
 pub(super) use client::*;
 pub(super) use errors::*;
+pub(super) use git::*;
+pub(super) use npmrc::*;
 pub(super) use options::*;
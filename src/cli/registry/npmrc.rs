@@ -0,0 +1,353 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+
+pub static NPMRC_FILENAME: &str = ".npmrc";
+
+const ENV_PREFIX: &str = "npm_config_";
+
+/// Credentials to send to a registry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryAuth {
+  /// `_authToken`, sent as a bearer token.
+  Token(String),
+  /// Base64 encoded `username:password`, from `_auth` or `username` + `_password`.
+  Basic(String),
+}
+
+/// Settings merged from the `.npmrc` files and the `npm_config_*` environment variables.
+#[derive(Debug, Default, Clone)]
+pub struct NpmConfig {
+  values: HashMap<String, String>,
+}
+
+impl NpmConfig {
+  /// Load the global, user and project `.npmrc` files, then the environment, later ones winning.
+  pub fn load(project_dir: Option<&Path>) -> Self {
+    let mut config = Self::default();
+
+    let config_files = [
+      Self::global_config_path(),
+      Self::user_config_path(),
+      project_dir.map(|dir| dir.join(NPMRC_FILENAME)),
+    ];
+
+    for contents in config_files
+      .into_iter()
+      .flatten()
+      .filter_map(|path| fs::read_to_string(path).ok())
+    {
+      config
+        .values
+        .extend(parse(&contents, |name| env::var(name).ok()));
+    }
+
+    config.values.extend(env_values(env::vars()));
+
+    config
+  }
+
+  #[cfg(test)]
+  pub fn from_values(values: HashMap<String, String>) -> Self {
+    Self { values }
+  }
+
+  /// Override settings, e.g. with the registries of the pushapp config.
+  pub fn extend<I>(&mut self, values: I)
+  where
+    I: IntoIterator<Item = (String, String)>,
+  {
+    self.values.extend(values);
+  }
+
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self.values.get(key).map(String::as_str)
+  }
+
+  /// Default registry, always with a trailing slash.
+  pub fn registry(&self) -> String {
+    with_trailing_slash(self.get("registry").unwrap_or(DEFAULT_REGISTRY))
+  }
+
+  /// Registry serving `package_name`, honoring `@scope:registry` mappings.
+  pub fn registry_for(&self, package_name: &str) -> String {
+    package_name
+      .starts_with('@')
+      .then(|| package_name.split('/').next())
+      .flatten()
+      .and_then(|scope| self.get(&format!("{scope}:registry")))
+      .map_or_else(|| self.registry(), with_trailing_slash)
+  }
+
+  /// Credentials configured for `registry_url`, matching the most specific `//host/path/:` prefix.
+  pub fn auth_for(&self, registry_url: &str) -> Option<RegistryAuth> {
+    let mut nerf_dart = to_nerf_dart(registry_url)?;
+
+    loop {
+      if let Some(token) = self.get(&format!("{nerf_dart}:_authToken")) {
+        return Some(RegistryAuth::Token(token.to_owned()));
+      }
+
+      if let Some(auth) = self.get(&format!("{nerf_dart}:_auth")) {
+        return Some(RegistryAuth::Basic(auth.to_owned()));
+      }
+
+      let username = self.get(&format!("{nerf_dart}:username"));
+      let password = self
+        .get(&format!("{nerf_dart}:_password"))
+        .and_then(|password| BASE64.decode(password).ok())
+        .and_then(|password| String::from_utf8(password).ok());
+      if let (Some(username), Some(password)) = (username, password) {
+        return Some(RegistryAuth::Basic(
+          BASE64.encode(format!("{username}:{password}")),
+        ));
+      }
+
+      // Walk up the path: `//host/a/b/` -> `//host/a/` -> `//host/`
+      let trimmed = nerf_dart.trim_end_matches('/');
+      match trimmed.rfind('/') {
+        Some(index) if index > 1 => nerf_dart = trimmed[..=index].to_owned(),
+        _ => return None,
+      }
+    }
+  }
+
+  pub fn strict_ssl(&self) -> Option<bool> {
+    self.get("strict-ssl").map(|value| value != "false")
+  }
+
+  pub fn max_sockets(&self) -> Option<usize> {
+    self
+      .get("maxsockets")?
+      .parse()
+      .ok()
+      .filter(|sockets| *sockets > 0)
+  }
+
+  pub fn fetch_retries(&self) -> Option<u32> {
+    self.get("fetch-retries")?.parse().ok()
+  }
+
+  pub fn fetch_retry_min_timeout(&self) -> Option<u64> {
+    self.get("fetch-retry-mintimeout")?.parse().ok()
+  }
+
+  pub fn fetch_retry_max_timeout(&self) -> Option<u64> {
+    self.get("fetch-retry-maxtimeout")?.parse().ok()
+  }
+
+  fn global_config_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var("NPM_CONFIG_GLOBALCONFIG") {
+      return Some(PathBuf::from(path));
+    }
+
+    let prefix = env::var("NPM_CONFIG_PREFIX").map(PathBuf::from).ok();
+    if cfg!(windows) {
+      prefix
+        .or_else(|| dirs::data_dir().map(|dir| dir.join("npm")))
+        .map(|prefix| prefix.join("etc").join("npmrc"))
+    } else {
+      Some(
+        prefix
+          .unwrap_or_else(|| PathBuf::from("/usr/local"))
+          .join("etc")
+          .join("npmrc"),
+      )
+    }
+  }
+
+  fn user_config_path() -> Option<PathBuf> {
+    env::var("NPM_CONFIG_USERCONFIG")
+      .map(PathBuf::from)
+      .ok()
+      .or_else(|| dirs::home_dir().map(|home| home.join(NPMRC_FILENAME)))
+  }
+}
+
+/// Parse an ini-like `.npmrc` file, expanding `${VAR}` references with `lookup`.
+fn parse<F>(contents: &str, lookup: F) -> HashMap<String, String>
+where
+  F: Fn(&str) -> Option<String>,
+{
+  contents
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with([';', '#', '[']))
+    .filter_map(|line| line.split_once('='))
+    .map(|(key, value)| {
+      let value = value.trim();
+      let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+
+      (expand_env(key.trim(), &lookup), expand_env(value, &lookup))
+    })
+    .collect()
+}
+
+fn expand_env<F>(value: &str, lookup: &F) -> String
+where
+  F: Fn(&str) -> Option<String>,
+{
+  let mut expanded = String::new();
+  let mut rest = value;
+
+  while let Some(start) = rest.find("${") {
+    let Some(end) = rest[start..].find('}') else {
+      break;
+    };
+
+    expanded.push_str(&rest[..start]);
+    // `${VAR?}` is the "optional" syntax, both resolve to an empty string when unset
+    let name = rest[start + 2..start + end].trim_end_matches('?');
+    expanded.push_str(&lookup(name).unwrap_or_default());
+    rest = &rest[start + end + 1..];
+  }
+
+  expanded.push_str(rest);
+  expanded
+}
+
+/// Settings of the `npm_config_*` environment variables.
+fn env_values<I>(vars: I) -> impl Iterator<Item = (String, String)>
+where
+  I: IntoIterator<Item = (String, String)>,
+{
+  vars.into_iter().filter_map(|(key, value)| {
+    let prefix = key.get(..ENV_PREFIX.len())?;
+    if !prefix.eq_ignore_ascii_case(ENV_PREFIX) {
+      return None;
+    }
+    Some((env_key_to_config_key(&key[ENV_PREFIX.len()..]), value))
+  })
+}
+
+/// Mirror npm's conversion: `npm_config_strict_ssl` -> `strict-ssl`, `npm_config__auth` -> `_auth`.
+/// Registry settings, `//host/:_authToken`, are kept as written.
+fn env_key_to_config_key(name: &str) -> String {
+  if name.starts_with("//") {
+    return name.to_owned();
+  }
+
+  name
+    .to_lowercase()
+    .char_indices()
+    .map(|(index, c)| if index > 0 && c == '_' { '-' } else { c })
+    .collect()
+}
+
+/// Strip the protocol of a registry URL: `https://host/path/` -> `//host/path/`.
+fn to_nerf_dart(registry_url: &str) -> Option<String> {
+  let url = url::Url::parse(registry_url).ok()?;
+  let host = url.host_str()?;
+  let port = url
+    .port()
+    .map(|port| format!(":{port}"))
+    .unwrap_or_default();
+
+  Some(with_trailing_slash(&format!(
+    "//{host}{port}{}",
+    url.path()
+  )))
+}
+
+fn with_trailing_slash(url: &str) -> String {
+  if url.ends_with('/') {
+    url.to_owned()
+  } else {
+    format!("{url}/")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn config(contents: &str) -> NpmConfig {
+    NpmConfig::from_values(parse(contents, |name| {
+      (name == "NPM_TOKEN").then(|| "secret".to_owned())
+    }))
+  }
+
+  #[test]
+  fn test_parse() {
+    let values = parse(
+      "; comment\n# comment\nregistry = \"https://registry.example.com\"\n//registry.example.com/:_authToken=${NPM_TOKEN}\nempty=${MISSING?}\n",
+      |name| (name == "NPM_TOKEN").then(|| "secret".to_owned()),
+    );
+
+    assert_eq!(values.len(), 3);
+    assert_eq!(values["registry"], "https://registry.example.com");
+    assert_eq!(values["//registry.example.com/:_authToken"], "secret");
+    assert_eq!(values["empty"], "");
+  }
+
+  #[test]
+  fn test_registry_for_scope() {
+    let config = config("@company:registry=https://npm.company.com/repo\n");
+
+    assert_eq!(config.registry_for("react"), DEFAULT_REGISTRY);
+    assert_eq!(config.registry_for("@types/node"), DEFAULT_REGISTRY);
+    assert_eq!(
+      config.registry_for("@company/ui"),
+      "https://npm.company.com/repo/"
+    );
+  }
+
+  #[test]
+  fn test_auth_for_registry() {
+    let config = config(
+      "//npm.company.com/:_authToken=${NPM_TOKEN}\n\
+       //other.com/repo/:username=user\n\
+       //other.com/repo/:_password=cGFzcw==\n",
+    );
+
+    assert_eq!(
+      config.auth_for("https://npm.company.com/repo/"),
+      Some(RegistryAuth::Token("secret".to_owned()))
+    );
+    assert_eq!(
+      config.auth_for("https://other.com/repo"),
+      Some(RegistryAuth::Basic(BASE64.encode("user:pass")))
+    );
+    assert_eq!(config.auth_for("https://other.com/"), None);
+    assert_eq!(config.auth_for(DEFAULT_REGISTRY), None);
+  }
+
+  #[test]
+  fn test_env_key_to_config_key() {
+    assert_eq!(env_key_to_config_key("strict_ssl"), "strict-ssl");
+    assert_eq!(env_key_to_config_key("_auth"), "_auth");
+    assert_eq!(
+      env_key_to_config_key("//npm.company.com/:_authToken"),
+      "//npm.company.com/:_authToken"
+    );
+  }
+
+  #[test]
+  fn test_auth_from_env() {
+    let config = NpmConfig::from_values(
+      env_values([
+        (
+          "npm_config_//npm.company.com/:_authToken".to_owned(),
+          "secret".to_owned(),
+        ),
+        ("NPM_CONFIG_STRICT_SSL".to_owned(), "false".to_owned()),
+        ("HOME".to_owned(), "/home/user".to_owned()),
+      ])
+      .collect(),
+    );
+
+    assert_eq!(
+      config.auth_for("https://npm.company.com/"),
+      Some(RegistryAuth::Token("secret".to_owned()))
+    );
+    assert_eq!(config.get("strict-ssl"), Some("false"));
+    assert_eq!(config.get("HOME"), None);
+  }
+}
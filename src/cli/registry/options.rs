@@ -1,8 +1,15 @@
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RegistryClientOptions {
   pub max_sockets: usize,
   pub timeout: u64,
   pub strict_ssl: bool,
+  /// Retries of a request failing with a transient error: timeout, 429 or 5xx.
+  pub retries: u32,
+  /// Delay before the first retry in milliseconds, doubled on each of the next ones.
+  pub retry_min_timeout: u64,
+  /// Longest delay between two attempts in milliseconds. A request asked to wait longer with
+  /// `Retry-After` isn't retried.
+  pub retry_max_timeout: u64,
 }
 
 impl Default for RegistryClientOptions {
@@ -11,6 +18,9 @@ impl Default for RegistryClientOptions {
       max_sockets: 12,
       timeout: 5 * 60 * 1000,
       strict_ssl: true,
+      retries: 2,
+      retry_min_timeout: 1000,
+      retry_max_timeout: 30 * 1000,
     }
   }
 }
@@ -0,0 +1,334 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use colored::Colorize;
+use std::fmt::Display;
+
+use super::{
+  package_info::{diff_group_title, PackageInfo},
+  versions::SemverDiff,
+};
+
+#[derive(Debug, Clone, Copy, ValueEnum, Default, PartialEq)]
+pub enum OutputFormat {
+  #[default]
+  Table,
+  Json,
+  Ndjson,
+  Markdown,
+}
+
+impl OutputFormat {
+  /// Whether the output is meant to be consumed by other tools rather than read by a human.
+  pub fn is_machine_readable(self) -> bool {
+    matches!(self, OutputFormat::Json | OutputFormat::Ndjson)
+  }
+}
+
+/// Print a progress message, keeping stdout clean for machine-readable formats.
+pub fn print_status(format: Option<OutputFormat>, message: impl Display) {
+  match format {
+    Some(format) if format.is_machine_readable() => eprintln!("{message}"),
+    _ => println!("{message}"),
+  }
+}
+
+/// Render the updatable packages in the given output format.
+pub fn render(format: OutputFormat, packages: &[PackageInfo]) -> Result<String> {
+  let output = match format {
+    OutputFormat::Table => render_table(packages),
+    OutputFormat::Json => serde_json::to_string_pretty(packages)?,
+    OutputFormat::Ndjson => packages
+      .iter()
+      .map(serde_json::to_string)
+      .collect::<Result<Vec<_>, _>>()?
+      .join("\n"),
+    OutputFormat::Markdown => render_markdown(packages),
+  };
+
+  Ok(output)
+}
+
+const HEADERS: [&str; 10] = [
+  "Package",
+  "Current",
+  "Target",
+  "Diff",
+  "Type",
+  "Workspace",
+  "Manager",
+  "Security",
+  "Current Published",
+  "Target Published",
+];
+
+/// Indices of the columns to show: the package manager only for global packages, the advisories
+/// and publish times only when they're known.
+fn columns(packages: &[PackageInfo]) -> Vec<usize> {
+  let has_managers = packages.iter().any(|package| package.manager.is_some());
+  let has_advisories = packages
+    .iter()
+    .any(|package| !package.advisories.is_empty());
+  let has_times = packages
+    .iter()
+    .any(|package| package.published_at.is_some() || package.current_published_at.is_some());
+
+  (0..HEADERS.len())
+    .filter(|column| match column {
+      6 => has_managers,
+      7 => has_advisories,
+      8 | 9 => has_times,
+      _ => true,
+    })
+    .collect()
+}
+
+fn headers(packages: &[PackageInfo]) -> Vec<&'static str> {
+  columns(packages)
+    .into_iter()
+    .map(|column| HEADERS[column])
+    .collect()
+}
+
+fn rows(packages: &[PackageInfo]) -> Vec<Vec<String>> {
+  let columns = columns(packages);
+
+  packages
+    .iter()
+    .map(|package| {
+      let mut row = vec![
+        package.pkg_name.clone(),
+        package.current_version.clone(),
+        package.latest_version.clone(),
+        package
+          .diff
+          .map(|diff| diff.to_string())
+          .unwrap_or_default(),
+        package
+          .dep_type
+          .map(|dep_type| dep_type.to_string())
+          .unwrap_or_default(),
+        package.workspace.clone().unwrap_or_default(),
+        package
+          .manager
+          .map(|manager| manager.to_string())
+          .unwrap_or_default(),
+        package
+          .severity()
+          .map(|severity| format!("{severity} ({})", package.advisories.len()))
+          .unwrap_or_default(),
+        package.current_published().unwrap_or_default(),
+        package.published().unwrap_or_default(),
+      ];
+      columns
+        .iter()
+        .map(|&column| std::mem::take(&mut row[column]))
+        .collect()
+    })
+    .collect()
+}
+
+/// Table of the updates, grouped under a title per semver difference.
+///
+/// Packages are expected to be sorted by semver difference already.
+fn render_table(packages: &[PackageInfo]) -> String {
+  let headers = headers(packages);
+  let rows = rows(packages);
+
+  let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+  for row in &rows {
+    for (width, cell) in widths.iter_mut().zip(row) {
+      *width = (*width).max(cell.chars().count());
+    }
+  }
+
+  let header = headers
+    .iter()
+    .zip(&widths)
+    .map(|(header, width)| format!("{header:<width$}").bold().to_string())
+    .collect::<Vec<_>>()
+    .join("  ");
+
+  let mut lines = vec![header.trim_end().to_string()];
+  let mut current_group = None;
+  for (row, package) in rows.iter().zip(packages) {
+    if current_group != Some(package.diff) {
+      current_group = Some(package.diff);
+      lines.push(format!("\n{}", diff_group_title(package.diff)));
+    }
+
+    let line = row
+      .iter()
+      .zip(&widths)
+      .enumerate()
+      .map(|(column, (cell, width))| {
+        let cell = format!("{cell:<width$}");
+        match (column, package.diff) {
+          (2, Some(SemverDiff::Major)) => cell.bright_red().bold().to_string(),
+          (2, Some(SemverDiff::Minor)) => cell.bright_yellow().bold().to_string(),
+          (2, _) => cell.bright_green().bold().to_string(),
+          _ => cell,
+        }
+      })
+      .collect::<Vec<_>>()
+      .join("  ");
+    lines.push(line.trim_end().to_string());
+  }
+
+  lines.join("\n")
+}
+
+fn render_markdown(packages: &[PackageInfo]) -> String {
+  let headers = headers(packages);
+  let header = markdown_row(&headers);
+  let separator = format!("|{}|", vec!["---"; headers.len()].join("|"));
+  let lines = rows(packages).into_iter().map(|row| markdown_row(&row));
+
+  [header, separator]
+    .into_iter()
+    .chain(lines)
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// A table row, escaping the pipes of ranges such as `^17 || ^18`.
+fn markdown_row(cells: &[impl AsRef<str>]) -> String {
+  let cells: Vec<String> = cells
+    .iter()
+    .map(|cell| cell.as_ref().replace('|', "\\|"))
+    .collect();
+  format!("| {} |", cells.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cli::{package_json::DependencyType, package_manager::PackageManager};
+
+  fn packages() -> Vec<PackageInfo> {
+    vec![PackageInfo {
+      pkg_name: "react".to_owned(),
+      current_version: "^18.2.0".to_owned(),
+      latest_version: "19.0.0".to_owned(),
+      diff: Some(SemverDiff::Major),
+      dep_type: Some(DependencyType::Dependencies),
+      workspace: Some("web".to_owned()),
+      ..Default::default()
+    }]
+  }
+
+  #[test]
+  fn test_render_json() {
+    let output = render(OutputFormat::Json, &packages()).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(
+      json,
+      serde_json::json!([{
+        "name": "react",
+        "current": "^18.2.0",
+        "target": "19.0.0",
+        "diff": "major",
+        "depType": "dependencies",
+        "workspace": "web"
+      }])
+    );
+  }
+
+  #[test]
+  fn test_render_ndjson() {
+    let mut updates = packages();
+    updates.push(PackageInfo {
+      pkg_name: "eslint".to_owned(),
+      workspace: None,
+      ..packages().pop().unwrap()
+    });
+
+    let output = render(OutputFormat::Ndjson, &updates).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with(r#"{"name":"eslint""#));
+  }
+
+  #[test]
+  fn test_render_markdown() {
+    let output = render(OutputFormat::Markdown, &packages()).unwrap();
+
+    assert_eq!(
+      output,
+      "| Package | Current | Target | Diff | Type | Workspace |\n\
+       |---|---|---|---|---|---|\n\
+       | react | ^18.2.0 | 19.0.0 | major | dependencies | web |"
+    );
+  }
+
+  #[test]
+  fn test_render_markdown_escapes_pipes() {
+    let updates = vec![PackageInfo {
+      current_version: "^17 || ^18".to_owned(),
+      latest_version: "^17 || ^18 || ^19".to_owned(),
+      ..packages().pop().unwrap()
+    }];
+
+    let output = render(OutputFormat::Markdown, &updates).unwrap();
+
+    assert!(output.ends_with(
+      "| react | ^17 \\|\\| ^18 | ^17 \\|\\| ^18 \\|\\| ^19 | major | dependencies | web |"
+    ));
+  }
+
+  #[test]
+  fn test_render_markdown_with_publish_times() {
+    let updates = vec![PackageInfo {
+      published_at: Some(chrono::Utc::now() - chrono::TimeDelta::days(3)),
+      ..packages().pop().unwrap()
+    }];
+
+    let output = render(OutputFormat::Markdown, &updates).unwrap();
+
+    assert!(output.starts_with(
+      "| Package | Current | Target | Diff | Type | Workspace | Current Published | Target Published |"
+    ));
+    assert!(output.contains("| web |  | "));
+    assert!(output.ends_with(", 3 days ago |"));
+  }
+
+  #[test]
+  fn test_render_markdown_with_package_manager() {
+    let updates = vec![PackageInfo {
+      workspace: None,
+      manager: Some(PackageManager::Pnpm),
+      ..packages().pop().unwrap()
+    }];
+
+    let output = render(OutputFormat::Markdown, &updates).unwrap();
+
+    assert!(
+      output.starts_with("| Package | Current | Target | Diff | Type | Workspace | Manager |")
+    );
+    assert!(output.ends_with("| dependencies |  | pnpm |"));
+  }
+
+  #[test]
+  fn test_render_table_groups_by_diff() {
+    let mut updates = packages();
+    updates.push(PackageInfo {
+      pkg_name: "eslint".to_owned(),
+      diff: Some(SemverDiff::Patch),
+      ..packages().pop().unwrap()
+    });
+
+    // Colors depend on the terminal, compare the plain text
+    let output = render(OutputFormat::Table, &updates).unwrap();
+    let output = regex::Regex::new(r"\x1b\[[0-9;]*m")
+      .unwrap()
+      .replace_all(&output, "");
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(lines[1], "");
+    assert_eq!(lines[2], "Major");
+    assert!(lines[3].starts_with("react"));
+    assert_eq!(lines[5], "Patch");
+    assert!(lines[6].starts_with("eslint"));
+  }
+}
@@ -1,18 +1,79 @@
+use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use std::hash::Hash;
+use std::io::{self, IsTerminal, Write};
+use std::pin::pin;
+use std::process::ExitCode;
 use std::sync::Arc;
 
 use anyhow::Result;
 use colored::Colorize;
-use futures::{stream::FuturesUnordered, StreamExt};
-use tokio::task::{self, JoinHandle};
+use futures::{stream, Stream, StreamExt};
+use nodejs_semver::Version;
+use tokio::task::{self, JoinError};
 
 use super::{
+  changelog::{format_release_notes, ChangelogFetcher},
+  deprecations::Deprecation,
+  filters::filter_dependencies,
   flags::Flags,
-  package_info::PackageInfo,
-  package_json::{PackageDependencies, PackageJsonManager},
-  prompt::display_update,
-  registry::RegistryClient,
+  globals::get_global_deps,
+  package_info::{colored_severity, PackageInfo},
+  package_json::{Dependency, DependencyType, PackageJsonManager},
+  peers::{check_peer_dependencies, PeerDependent},
+  prompt::{confirm_peer_conflicts, display_update},
+  registry::{
+    Advisory, AuditRequest, MetadataCache, NpmConfig, PackageLookup, RegistryClient, RegistryError,
+  },
+  report::{print_status, render},
+  versions::{current_version_of, DependencySpec, UpdateRule},
 };
 
+/// Exit status when some dependencies couldn't be checked against the registry.
+const LOOKUP_FAILURE_EXIT_CODE: u8 = 2;
+
+/// A dependency whose registry lookup failed.
+#[derive(Debug)]
+pub struct PackageFailure {
+  pub dependency: Dependency,
+  /// Shared by the dependencies of the same lookup.
+  pub error: Arc<RegistryError>,
+}
+
+/// A dependency checked against the registry.
+#[derive(Debug)]
+struct CheckedDependency {
+  update: Option<PackageInfo>,
+  /// Peer dependencies of an installed dependency, for the peer dependencies check.
+  dependent: Option<PeerDependent>,
+  /// Deprecated current or target versions.
+  deprecations: Vec<Deprecation>,
+}
+
+type UpdateResult = Result<CheckedDependency, PackageFailure>;
+
+/// Dependencies checked once: the name, the specifier and whether it is a peer range, which
+/// isn't audited.
+type LookupKey = (String, String, bool);
+
+/// Where the versions of a dependency come from, fetched once for all the dependencies
+/// resolving to the same package.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PackageSource {
+  Registry(String),
+  Git(String),
+}
+
+/// What to do with the available updates.
+#[derive(Debug, PartialEq)]
+enum UpdateAction {
+  /// Only report them, exiting with the given status: a failure with `--check`.
+  Report(ExitCode),
+  /// Install all of them without prompting, with `--yes`.
+  InstallAll,
+  /// Prompt the updates to install.
+  Prompt,
+}
+
 #[derive(Debug)]
 pub struct UpdateChecker {
   pkg_manager: PackageJsonManager,
@@ -22,98 +83,921 @@ pub struct UpdateChecker {
 
 impl UpdateChecker {
   pub fn new(pkg_manager: PackageJsonManager, flags: Arc<Flags>) -> Self {
+    let project_dir = pkg_manager
+      .file_path
+      .as_ref()
+      .and_then(|path| path.parent());
+    let mut npm_config = NpmConfig::load(project_dir);
+    npm_config.extend(
+      flags
+        .registry
+        .iter()
+        .map(|registry| ("registry".to_string(), registry.clone()))
+        .chain(
+          flags
+            .registries
+            .iter()
+            .map(|(scope, registry)| (format!("{scope}:registry"), registry.clone())),
+        )
+        .chain(
+          flags
+            .concurrency
+            .map(|concurrency| ("maxsockets".to_string(), concurrency.to_string())),
+        ),
+    );
+    let mut client = RegistryClient::new(npm_config);
+    client.full_metadata = flags.time || flags.changelog;
+    client.cache = MetadataCache::from_env();
+    client.cache_mode = flags.cache_mode();
+
     Self {
       pkg_manager,
       flags,
-      client: Arc::new(RegistryClient::default()),
+      client: Arc::new(client),
     }
   }
 
-  pub async fn run(&self) -> Result<()> {
-    println!("🔍 {}", "Checking updates...".bright_yellow());
+  pub async fn run(&self) -> Result<ExitCode> {
+    print_status(
+      self.flags.format,
+      format!("🔍 {}", "Checking updates...".bright_yellow()),
+    );
 
     let deps = if self.flags.global {
-      self::PackageJsonManager::get_global_deps()?
+      get_global_deps(self.flags.package_manager)?
     } else {
       self.pkg_manager.get_local_deps()
     };
 
-    let tasks = self.fetch_updates(deps);
-    if tasks.is_empty() {
-      println!("{}", "📦 No dependencies found.".bright_red());
-      return Ok(());
+    // Filter before spawning any request, the dependencies left out only matter to the peer check
+    let (deps, left_out) = filter_dependencies(deps, &self.flags.filter, &self.flags.reject);
+    let (deps, skipped): (Vec<_>, Vec<_>) = deps
+      .into_iter()
+      .partition(|dep| DependencySpec::parse(&dep.version).skip_reason().is_none());
+    self.report_skipped(&skipped);
+
+    let advisories = if self.flags.audit || self.flags.security_only {
+      self.fetch_advisories(&deps).await
+    } else {
+      HashMap::new()
+    };
+    let vulnerable: Vec<(Dependency, Vec<Advisory>)> = deps
+      .iter()
+      .map(|dep| (dep.clone(), advisories_of(dep, &advisories)))
+      .filter(|(_, advisories)| !advisories.is_empty())
+      .collect();
+
+    let total = deps.len();
+    if total == 0 {
+      print_status(self.flags.format, "📦 No dependencies found.".bright_red());
+      return Ok(ExitCode::SUCCESS);
     }
 
-    println!(
-      "{}",
-      format!("📦 Found {} dependencies.", tasks.len()).bright_green()
-    );
+    let workspaces_count = self.pkg_manager.workspaces.len();
+    let found_message = if workspaces_count > 0 {
+      format!("📦 Found {total} dependencies in {workspaces_count} workspaces.")
+    } else {
+      format!("📦 Found {total} dependencies.")
+    };
+    print_status(self.flags.format, found_message.bright_green());
 
-    let updatable_packages = self.process_update_stream(tasks).await;
-    self.handle_updatable_packages(updatable_packages)
+    let tasks = self.fetch_updates(deps, Arc::new(advisories));
+    let (updatable_packages, dependents, deprecations, failures) =
+      self.process_update_stream(tasks, total).await;
+    self.report_failures(&failures);
+    self.report_unfixed(&vulnerable, &updatable_packages);
+    self.report_deprecations(&deprecations);
+
+    let exit_code = self
+      .handle_updatable_packages(updatable_packages, &dependents, left_out)
+      .await?;
+    if failures.is_empty() {
+      Ok(exit_code)
+    } else {
+      Ok(ExitCode::from(LOOKUP_FAILURE_EXIT_CODE))
+    }
   }
 
+  /// Look the dependencies up, as many packages at once as the registry sockets allow. Each
+  /// package is fetched once, the dependencies sharing the same specifier, in several workspaces,
+  /// being checked once.
   fn fetch_updates(
     &self,
-    deps: PackageDependencies,
-  ) -> FuturesUnordered<JoinHandle<Option<PackageInfo>>> {
-    deps
-      .into_iter()
-      .map(|(name, version)| {
-        let client = self.client.clone();
-        let flags = self.flags.clone();
-        task::spawn(async move {
-          match client.get_package_info(&name, &version, &flags).await {
-            Ok(Some(info)) => Some(info),
-            Ok(None) => None,
-            Err(e) => {
-              #[cfg(debug_assertions)]
-              eprintln!("{}", format!("❌ {e}").bright_red());
-              None
-            }
-          }
-        })
+    deps: Vec<Dependency>,
+    advisories: Arc<HashMap<String, Vec<Advisory>>>,
+  ) -> impl Stream<Item = Result<Vec<UpdateResult>, JoinError>> {
+    let packages = group_by(group_by(deps, lookup_key), |group| {
+      package_source(&group[0]).0
+    });
+
+    let client = self.client.clone();
+    let flags = self.flags.clone();
+    let concurrency = client.options.max_sockets;
+    stream::iter(packages)
+      .map(move |groups| {
+        let client = client.clone();
+        let flags = flags.clone();
+        let advisories = advisories.clone();
+        task::spawn(async move { check_package(&client, &flags, groups, &advisories).await })
       })
-      .collect()
+      .buffer_unordered(concurrency)
   }
 
   async fn process_update_stream(
     &self,
-    mut tasks: FuturesUnordered<JoinHandle<Option<PackageInfo>>>,
-  ) -> Vec<PackageInfo> {
+    tasks: impl Stream<Item = Result<Vec<UpdateResult>, JoinError>>,
+    total: usize,
+  ) -> (
+    Vec<PackageInfo>,
+    Vec<PeerDependent>,
+    Vec<Deprecation>,
+    Vec<PackageFailure>,
+  ) {
     let mut pkg_infos = Vec::new();
+    let mut dependents = Vec::new();
+    let mut deprecations = Vec::new();
+    let mut failures = Vec::new();
+
+    let mut checked_count = 0;
+    let mut tasks = pin!(tasks);
 
     // Process each task as it completes
     while let Some(task) = tasks.next().await {
-      match task {
-        Ok(Some(pkg_info)) => pkg_infos.push(pkg_info),
-        Ok(None) => {} // Skip None results
+      let results = match task {
+        Ok(results) => results,
         Err(e) => {
           eprintln!("❌ Task failed to execute: {e}");
+          continue;
+        }
+      };
+
+      checked_count += results.len();
+      self.show_progress(checked_count, total);
+      for result in results {
+        match result {
+          Ok(checked) => {
+            // Up to date packages are only kept for the peer dependencies check
+            pkg_infos.extend(checked.update);
+            dependents.extend(checked.dependent);
+            deprecations.extend(checked.deprecations);
+          }
+          Err(failure) => failures.push(failure),
         }
       }
     }
+    self.show_progress(total, total);
+
+    failures.sort_by(|a, b| {
+      (&a.dependency.workspace, &a.dependency.name)
+        .cmp(&(&b.dependency.workspace, &b.dependency.name))
+    });
+
+    deprecations.sort_by(|a, b| {
+      (&a.workspace, &a.name, a.is_target).cmp(&(&b.workspace, &b.name, b.is_target))
+    });
 
-    pkg_infos
+    (pkg_infos, dependents, deprecations, failures)
   }
 
-  fn handle_updatable_packages(&self, mut updatable_packages: Vec<PackageInfo>) -> Result<()> {
+  /// Query the security advisories of the current versions, going on without them when the
+  /// registry doesn't support audits.
+  async fn fetch_advisories(&self, deps: &[Dependency]) -> HashMap<String, Vec<Advisory>> {
+    let mut request: AuditRequest = HashMap::new();
+    for (name, version) in deps.iter().filter_map(audited_version) {
+      let versions = request.entry(name).or_default();
+      let version = version.to_string();
+      if !versions.contains(&version) {
+        versions.push(version);
+      }
+    }
+
+    if request.is_empty() {
+      return HashMap::new();
+    }
+    if !self.client.cache_mode.uses_network() {
+      eprintln!(
+        "{}",
+        "⚠️  The security advisories can't be fetched offline.".bright_yellow()
+      );
+      return HashMap::new();
+    }
+
+    self
+      .client
+      .fetch_advisories(&request)
+      .await
+      .unwrap_or_else(|e| {
+        eprintln!(
+          "{}",
+          format!("⚠️  Couldn't fetch the security advisories: {e}").bright_yellow()
+        );
+        HashMap::new()
+      })
+  }
+
+  /// Print the vulnerable dependencies that the available updates don't fix.
+  fn report_unfixed(&self, vulnerable: &[(Dependency, Vec<Advisory>)], updates: &[PackageInfo]) {
+    let unfixed: Vec<_> = vulnerable
+      .iter()
+      .filter(|(dep, advisories)| {
+        let update = updates.iter().find(|update| {
+          update.pkg_name == dep.name
+            && update.workspace == dep.workspace
+            && update.dep_type == dep.dep_type
+        });
+        let target = update.and_then(|update| Version::parse(&update.latest_version).ok());
+        target.is_none_or(|target| advisories.iter().any(|advisory| advisory.affects(&target)))
+      })
+      .collect();
+
+    if unfixed.is_empty() {
+      return;
+    }
+
+    eprintln!(
+      "{}",
+      format!(
+        "\n⚠️  {} vulnerable package(s) can't be fixed by the proposed updates:",
+        unfixed.len()
+      )
+      .bright_red()
+    );
+    for (dep, advisories) in unfixed {
+      for advisory in advisories {
+        eprintln!(
+          "  {} {}@{}: {} {}",
+          colored_severity(advisory.severity),
+          dep.name,
+          dep.version,
+          advisory.title,
+          advisory.url.dimmed()
+        );
+      }
+    }
+  }
+
+  /// Print the deprecated versions in use or proposed, with the replacement their message suggests.
+  fn report_deprecations(&self, deprecations: &[Deprecation]) {
+    if deprecations.is_empty() {
+      return;
+    }
+
+    eprintln!(
+      "{}",
+      format!("\n⚠️  {} deprecated version(s):", deprecations.len()).bright_yellow()
+    );
+    for deprecation in deprecations {
+      eprintln!("  📛 {deprecation}");
+    }
+  }
+
+  /// List the dependencies that can't be checked, like local paths or tarballs.
+  fn report_skipped(&self, skipped: &[Dependency]) {
+    if skipped.is_empty() {
+      return;
+    }
+
+    print_status(
+      self.flags.format,
+      format!("⏭️  Skipping {} dependencies:", skipped.len()).dimmed(),
+    );
+    for dep in skipped {
+      let reason = DependencySpec::parse(&dep.version)
+        .skip_reason()
+        .unwrap_or_default();
+      print_status(
+        self.flags.format,
+        format!("  {} ({}): {reason}", dep.name, dep.version).dimmed(),
+      );
+    }
+  }
+
+  /// Print the lookups that failed, grouped by kind, so they don't pass for up to date packages.
+  fn report_failures(&self, failures: &[PackageFailure]) {
+    if failures.is_empty() {
+      return;
+    }
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for failure in failures {
+      *counts.entry(failure.error.kind()).or_default() += 1;
+    }
+    let summary = counts
+      .iter()
+      .map(|(kind, count)| format!("{count} {kind}"))
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    eprintln!(
+      "{}",
+      format!(
+        "\n⚠️  {} package(s) could not be checked ({summary}):",
+        failures.len()
+      )
+      .bright_red()
+    );
+
+    for failure in failures {
+      let workspace = failure
+        .dependency
+        .workspace
+        .clone()
+        .or_else(|| {
+          failure
+            .dependency
+            .manager
+            .map(|manager| manager.to_string())
+        })
+        .map(|owner| format!("[{owner}] ").dimmed().to_string())
+        .unwrap_or_default();
+      eprintln!("  ❌ {workspace}{}", failure.error);
+    }
+  }
+
+  async fn handle_updatable_packages(
+    &self,
+    mut updatable_packages: Vec<PackageInfo>,
+    dependents: &[PeerDependent],
+    left_out: Vec<Dependency>,
+  ) -> Result<ExitCode> {
+    let format = self.flags.format.unwrap_or_default();
+
     if updatable_packages.is_empty() {
-      println!("{}", "There are no updates available.".bright_blue());
-      return Ok(());
+      if format.is_machine_readable() {
+        println!("{}", render(format, &updatable_packages)?);
+      }
+      print_status(
+        self.flags.format,
+        "There are no updates available.".bright_blue(),
+      );
+      return Ok(ExitCode::SUCCESS);
+    }
+
+    // Group by semver difference, then by workspace with the root manifest first
+    updatable_packages.sort_by(|a, b| {
+      (
+        a.diff.is_none(),
+        a.diff,
+        &a.workspace,
+        a.manager,
+        &a.pkg_name,
+      )
+        .cmp(&(
+          b.diff.is_none(),
+          b.diff,
+          &b.workspace,
+          b.manager,
+          &b.pkg_name,
+        ))
+    });
+
+    let action = update_action(&self.flags);
+    if action != UpdateAction::Prompt {
+      println!("{}", render(format, &updatable_packages)?);
+    }
+
+    if self.flags.changelog {
+      self.show_changelogs(&updatable_packages).await;
     }
 
-    updatable_packages.sort_by(|a, b| a.pkg_name.cmp(&b.pkg_name));
+    let selected = match action {
+      UpdateAction::Report(exit_code) => {
+        if self.flags.check {
+          print_status(
+            self.flags.format,
+            format!("\n{} package(s) can be updated.", updatable_packages.len()).bright_yellow(),
+          );
+        }
+        return Ok(exit_code);
+      }
+      UpdateAction::InstallAll => Some(updatable_packages.clone()),
+      UpdateAction::Prompt => display_update(updatable_packages.clone()),
+    };
+    let selected = match selected {
+      Some(selected) => {
+        self
+          .check_peers(selected, &updatable_packages, dependents, left_out)
+          .await
+      }
+      None => None,
+    };
 
-    match display_update(updatable_packages) {
+    match selected {
       Some(selected) => {
         self.pkg_manager.install_deps(&selected)?;
       }
       None => {
-        println!("{}", "\nNo packages were updated.".bright_yellow());
+        print_status(
+          self.flags.format,
+          "\nNo packages were updated.".bright_yellow(),
+        );
       }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
+  }
+
+  /// Pull in the updates the peer dependencies of the selection require, and confirm the
+  /// remaining conflicts before installing.
+  async fn check_peers(
+    &self,
+    selected: Vec<PackageInfo>,
+    available: &[PackageInfo],
+    dependents: &[PeerDependent],
+    left_out: Vec<Dependency>,
+  ) -> Option<Vec<PackageInfo>> {
+    // Global packages don't share a manifest, they can't be each other's peers
+    if self.flags.global {
+      return Some(selected);
+    }
+
+    let mut dependents = dependents.to_vec();
+    dependents.extend(self.left_out_dependents(left_out).await);
+
+    let is_installed =
+      |package: &PackageInfo| package.dep_type.is_none_or(DependencyType::is_installed);
+    let (mut selected, constraints): (Vec<_>, Vec<_>) =
+      selected.into_iter().partition(is_installed);
+    let available: Vec<PackageInfo> = available
+      .iter()
+      .filter(|package| is_installed(package))
+      .cloned()
+      .collect();
+
+    let check = check_peer_dependencies(&mut selected, &available, &dependents);
+    selected.extend(constraints);
+
+    for package in &check.added {
+      print_status(
+        self.flags.format,
+        format!(
+          "🔗 Also updating {} to {} to satisfy peer dependencies.",
+          package.pkg_name, package.latest_version
+        )
+        .bright_blue(),
+      );
+    }
+
+    if check.conflicts.is_empty() {
+      return Some(selected);
+    }
+
+    eprintln!(
+      "{}",
+      "\n⚠️  The selected updates break some peer dependencies:".bright_red()
+    );
+    for conflict in &check.conflicts {
+      eprintln!("  ❌ {conflict}");
+    }
+
+    // `--yes` installs anyway, like the package managers reporting unmet peers
+    if self.flags.yes || confirm_peer_conflicts() {
+      Some(selected)
+    } else {
+      None
+    }
+  }
+
+  /// Peer dependencies of the installed dependencies left out by `--filter` and `--reject`, which
+  /// the selected updates must satisfy all the same.
+  async fn left_out_dependents(&self, left_out: Vec<Dependency>) -> Vec<PeerDependent> {
+    let left_out: Vec<Dependency> = left_out
+      .into_iter()
+      .filter(|dep| {
+        dep.dep_type.is_none_or(DependencyType::is_installed)
+          && DependencySpec::parse(&dep.version).skip_reason().is_none()
+      })
+      .collect();
+    if left_out.is_empty() {
+      return Vec::new();
+    }
+
+    // A failed lookup only leaves a dependent unchecked, like the skipped ones
+    let tasks: Vec<_> = self.fetch_updates(left_out, Arc::default()).collect().await;
+    tasks
+      .into_iter()
+      .flatten()
+      .flatten()
+      .filter_map(|result| result.ok()?.dependent)
+      .collect()
+  }
+
+  /// Print the release notes of every update, fetched as many at once as the registry sockets allow.
+  async fn show_changelogs(&self, packages: &[PackageInfo]) {
+    let fetcher = ChangelogFetcher::new(self.client.clone());
+    let notes: Vec<_> = stream::iter(packages)
+      .map(|package| fetcher.release_notes(package))
+      .buffered(self.client.options.max_sockets)
+      .collect()
+      .await;
+
+    for (package, notes) in packages.iter().zip(notes) {
+      print_status(self.flags.format, format!("\n📝 {package}").bold());
+      match notes {
+        Ok(Some((source, notes))) => {
+          print_status(self.flags.format, format!("From the {source}").dimmed());
+          print_status(self.flags.format, format_release_notes(&notes));
+        }
+        Ok(None) => print_status(self.flags.format, "No release notes found.".dimmed()),
+        Err(e) => print_status(
+          self.flags.format,
+          format!("Couldn't fetch the release notes: {e}").dimmed(),
+        ),
+      }
+    }
+  }
+
+  /// Keep a `checked/total` counter up to date on the terminal, erasing it once all are checked.
+  fn show_progress(&self, checked: usize, total: usize) {
+    if !io::stderr().is_terminal() {
+      return;
+    }
+
+    if checked < total {
+      eprint!(
+        "\r{}",
+        format!("⏳ Checked {checked}/{total} dependencies...").dimmed()
+      );
+    } else {
+      eprint!("\r\x1b[2K");
+    }
+    let _ = io::stderr().flush();
+  }
+}
+
+/// `--check` only reports the updates and fails, `--yes` skips the prompt, and a report format
+/// alone has nothing to prompt for.
+fn update_action(flags: &Flags) -> UpdateAction {
+  if flags.check {
+    UpdateAction::Report(ExitCode::FAILURE)
+  } else if flags.yes {
+    UpdateAction::InstallAll
+  } else if flags.format.is_some() {
+    UpdateAction::Report(ExitCode::SUCCESS)
+  } else {
+    UpdateAction::Prompt
+  }
+}
+
+fn lookup_key(dep: &Dependency) -> LookupKey {
+  (
+    dep.name.clone(),
+    dep.version.clone(),
+    dep.dep_type == Some(DependencyType::PeerDependencies),
+  )
+}
+
+/// Where the versions of a dependency come from, and its current range.
+fn package_source(dep: &Dependency) -> (PackageSource, &str) {
+  match DependencySpec::parse(&dep.version) {
+    DependencySpec::Alias { name, range } => (PackageSource::Registry(name.to_string()), range),
+    DependencySpec::Git {
+      url,
+      range: Some(range),
+    } => (PackageSource::Git(url), range),
+    _ => (PackageSource::Registry(dep.registry_name()), &dep.version),
+  }
+}
+
+/// Split the items into groups of the same key, in the order of their first item.
+fn group_by<T, K: Eq + Hash>(items: Vec<T>, key: impl Fn(&T) -> K) -> Vec<Vec<T>> {
+  let mut groups: Vec<Vec<T>> = Vec::new();
+  let mut group_of: HashMap<K, usize> = HashMap::new();
+  for item in items {
+    match group_of.entry(key(&item)) {
+      Entry::Occupied(entry) => groups[*entry.get()].push(item),
+      Entry::Vacant(entry) => {
+        entry.insert(groups.len());
+        groups.push(vec![item]);
+      }
+    }
+  }
+  groups
+}
+
+/// Fetch a package once, checking each group of dependencies sharing a lookup key against it.
+async fn check_package(
+  client: &RegistryClient,
+  flags: &Flags,
+  groups: Vec<Vec<Dependency>>,
+  advisories: &HashMap<String, Vec<Advisory>>,
+) -> Vec<UpdateResult> {
+  let Some(first) = groups.first().and_then(|group| group.first()) else {
+    return Vec::new();
+  };
+
+  let lookups: Vec<(Vec<Advisory>, UpdateRule)> = groups
+    .iter()
+    .map(|group| {
+      let dep = &group[0];
+      let advisories = advisories_of(dep, advisories);
+      let mut rule = flags.rule_for(&dep.name);
+      if flags.security_only {
+        rule.security_fix = Some(advisories.iter().filter_map(Advisory::range).collect());
+      }
+      (advisories, rule)
+    })
+    .collect();
+
+  let (source, _) = package_source(first);
+  let packument = match &source {
+    PackageSource::Registry(name) => {
+      let publish_times = lookups
+        .iter()
+        .any(|(_, rule)| rule.published_before.is_some());
+      client.get_packument(name, publish_times).await
+    }
+    PackageSource::Git(url) => client.get_git_packument(&first.name, url).await,
+  }
+  .map_err(Arc::new);
+
+  let mut results = Vec::new();
+  for (group, (advisories, rule)) in groups.into_iter().zip(lookups) {
+    let dep = &group[0];
+    let name = match &source {
+      PackageSource::Registry(name) => name,
+      PackageSource::Git(_) => &dep.name,
+    };
+    let lookup = match &packument {
+      Ok(packument) => {
+        RegistryClient::find_update(name, package_source(dep).1, packument, &rule).map_err(Arc::new)
+      }
+      Err(error) => Err(error.clone()),
+    };
+
+    match lookup {
+      Ok(lookup) => results.extend(
+        group
+          .into_iter()
+          .map(|dep| Ok(check_dependency(dep, lookup.clone(), advisories.clone()))),
+      ),
+      Err(error) => results.extend(group.into_iter().map(|dependency| {
+        Err(PackageFailure {
+          dependency,
+          error: error.clone(),
+        })
+      })),
+    }
+  }
+  results
+}
+
+fn check_dependency(
+  dep: Dependency,
+  lookup: PackageLookup,
+  advisories: Vec<Advisory>,
+) -> CheckedDependency {
+  let mut deprecations = Vec::new();
+  if let Some(message) = lookup.deprecated {
+    deprecations.push(Deprecation {
+      name: dep.name.clone(),
+      version: dep.version.clone(),
+      workspace: dep.workspace.clone(),
+      message,
+      is_target: false,
+    });
+  }
+  // Peer ranges and overrides aren't installed, they have no peers to satisfy
+  let dependent = dep
+    .dep_type
+    .is_none_or(DependencyType::is_installed)
+    .then(|| PeerDependent {
+      name: dep.name.clone(),
+      workspace: dep.workspace.clone(),
+      current_version: dep.version.clone(),
+      peer_dependencies: lookup.peer_dependencies,
+    });
+  // Report the dependency under its manifest name and full specifier
+  let update = lookup
+    .update
+    .filter(|info| !dep.accepts(&info.latest_version))
+    .map(|info| PackageInfo {
+      pkg_name: dep.name,
+      current_version: dep.version,
+      dep_type: dep.dep_type,
+      workspace: dep.workspace,
+      manager: dep.manager,
+      advisories,
+      ..info
+    });
+  if let Some(update) = &update {
+    deprecations.extend(update.deprecated.clone().map(|message| Deprecation {
+      name: update.pkg_name.clone(),
+      version: update.latest_version.clone(),
+      workspace: update.workspace.clone(),
+      message,
+      is_target: true,
+    }));
+  }
+
+  CheckedDependency {
+    update,
+    dependent,
+    deprecations,
+  }
+}
+
+/// Registry package and version to audit for a dependency, none for git dependencies and peer
+/// ranges.
+fn audited_version(dep: &Dependency) -> Option<(String, Version)> {
+  if dep.dep_type == Some(DependencyType::PeerDependencies) {
+    return None;
+  }
+
+  match DependencySpec::parse(&dep.version) {
+    DependencySpec::Alias { name, range } => {
+      Some((name.to_owned(), current_version_of(range).ok()?))
+    }
+    DependencySpec::Registry(range) => Some((dep.registry_name(), current_version_of(range).ok()?)),
+    _ => None,
+  }
+}
+
+/// Advisories affecting the current version of a dependency.
+fn advisories_of(dep: &Dependency, advisories: &HashMap<String, Vec<Advisory>>) -> Vec<Advisory> {
+  let Some((name, version)) = audited_version(dep) else {
+    return Vec::new();
+  };
+
+  advisories
+    .get(&name)
+    .into_iter()
+    .flatten()
+    .filter(|advisory| advisory.affects(&version))
+    .cloned()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cli::report::OutputFormat;
+
+  fn dependency(workspace: Option<&str>) -> Dependency {
+    Dependency {
+      name: "react".to_string(),
+      version: "^18.0.0".to_string(),
+      dep_type: Some(DependencyType::Dependencies),
+      workspace: workspace.map(str::to_string),
+      manager: None,
+    }
+  }
+
+  #[tokio::test]
+  async fn test_check_package_fetches_once() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+      .mock("GET", "/react")
+      .with_body(
+        r#"{
+          "dist-tags": { "latest": "19.0.0" },
+          "versions": { "18.3.1": { "version": "18.3.1" }, "19.0.0": { "version": "19.0.0" } }
+        }"#,
+      )
+      .expect(1)
+      .create_async()
+      .await;
+    let client = RegistryClient::new(NpmConfig::from_values(HashMap::from([(
+      "registry".to_owned(),
+      server.url(),
+    )])));
+    let latest = Dependency {
+      version: "^19.0.0".to_string(),
+      workspace: Some("next".to_string()),
+      ..dependency(None)
+    };
+    let groups = vec![
+      vec![dependency(None), dependency(Some("web"))],
+      vec![latest],
+    ];
+
+    let results = check_package(&client, &Flags::default(), groups, &HashMap::new()).await;
+
+    mock.assert_async().await;
+    let updates: Vec<_> = results
+      .into_iter()
+      .map(|result| {
+        let checked = result.unwrap();
+        (
+          checked.dependent.unwrap().workspace,
+          checked.update.map(|update| update.latest_version),
+        )
+      })
+      .collect();
+    assert_eq!(
+      updates,
+      [
+        (None, Some("19.0.0".to_string())),
+        (Some("web".to_string()), Some("19.0.0".to_string())),
+        (Some("next".to_string()), None),
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_left_out_dependents() {
+    let mut server = mockito::Server::new_async().await;
+    server
+      .mock("GET", "/react-dom")
+      .with_body(
+        r#"{
+          "dist-tags": { "latest": "18.3.1" },
+          "versions": { "18.3.1": { "version": "18.3.1", "peerDependencies": { "react": "^18.3.1" } } }
+        }"#,
+      )
+      .create_async()
+      .await;
+    let checker = UpdateChecker {
+      pkg_manager: PackageJsonManager::default(),
+      client: Arc::new(RegistryClient::new(NpmConfig::from_values(HashMap::from(
+        [("registry".to_owned(), server.url())],
+      )))),
+      flags: Arc::new(Flags::default()),
+    };
+    let left_out = vec![
+      Dependency {
+        name: "react-dom".to_string(),
+        version: "^18.3.1".to_string(),
+        ..dependency(None)
+      },
+      Dependency {
+        name: "local".to_string(),
+        version: "file:../local".to_string(),
+        ..dependency(None)
+      },
+    ];
+
+    let dependents = checker.left_out_dependents(left_out).await;
+
+    assert_eq!(dependents.len(), 1);
+    assert_eq!(dependents[0].name, "react-dom");
+    assert_eq!(dependents[0].peer_dependencies["react"], "^18.3.1");
+  }
+
+  #[test]
+  fn test_update_action() {
+    let flags = |check, yes, format| Flags {
+      check,
+      yes,
+      format,
+      ..Default::default()
+    };
+
+    assert_eq!(
+      update_action(&flags(true, false, None)),
+      UpdateAction::Report(ExitCode::FAILURE)
+    );
+    assert_eq!(
+      update_action(&flags(true, true, Some(OutputFormat::Json))),
+      UpdateAction::Report(ExitCode::FAILURE)
+    );
+    assert_eq!(
+      update_action(&flags(false, true, None)),
+      UpdateAction::InstallAll
+    );
+    assert_eq!(
+      update_action(&flags(false, true, Some(OutputFormat::Json))),
+      UpdateAction::InstallAll
+    );
+    assert_eq!(
+      update_action(&flags(false, false, Some(OutputFormat::Table))),
+      UpdateAction::Report(ExitCode::SUCCESS)
+    );
+    assert_eq!(
+      update_action(&flags(false, false, None)),
+      UpdateAction::Prompt
+    );
+  }
+
+  #[test]
+  fn test_lookup_key() {
+    let peer = Dependency {
+      dep_type: Some(DependencyType::PeerDependencies),
+      ..dependency(None)
+    };
+
+    assert_eq!(
+      lookup_key(&dependency(None)),
+      lookup_key(&dependency(Some("web")))
+    );
+    assert_ne!(lookup_key(&dependency(None)), lookup_key(&peer));
+  }
+
+  #[test]
+  fn test_package_source() {
+    let alias = Dependency {
+      name: "react-17".to_string(),
+      version: "npm:react@^17.0.0".to_string(),
+      ..dependency(None)
+    };
+
+    assert_eq!(
+      package_source(&dependency(None)),
+      (PackageSource::Registry("react".to_string()), "^18.0.0")
+    );
+    assert_eq!(
+      package_source(&alias),
+      (PackageSource::Registry("react".to_string()), "^17.0.0")
+    );
   }
 }
@@ -0,0 +1,130 @@
+use anyhow::{format_err, Error, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// How long a version must have been published before being proposed: `30m`, `12h`, `7d`, `2w`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct MinAge(pub TimeDelta);
+
+impl FromStr for MinAge {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self> {
+    let invalid = || format_err!("Invalid age \"{s}\", expected a duration such as 12h, 7d or 2w.");
+
+    let unit_start = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let amount: i64 = s[..unit_start].parse().map_err(|_| invalid())?;
+
+    // Days when the unit is omitted
+    let age = match &s[unit_start..] {
+      "m" => TimeDelta::try_minutes(amount),
+      "h" => TimeDelta::try_hours(amount),
+      "" | "d" => TimeDelta::try_days(amount),
+      "w" => TimeDelta::try_weeks(amount),
+      _ => None,
+    };
+
+    age.map(MinAge).ok_or_else(invalid)
+  }
+}
+
+impl MinAge {
+  /// Latest publish time old enough, the oldest possible one when the age goes past it.
+  pub fn published_before(self, now: DateTime<Utc>) -> DateTime<Utc> {
+    now
+      .checked_sub_signed(self.0)
+      .unwrap_or(DateTime::<Utc>::MIN_UTC)
+  }
+}
+
+impl TryFrom<String> for MinAge {
+  type Error = Error;
+
+  fn try_from(value: String) -> Result<Self> {
+    value.parse()
+  }
+}
+
+/// Time elapsed since `published_at`, in the largest fitting unit: `3 days ago`.
+pub fn format_age(published_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+  let age = now - published_at;
+  let (amount, unit) = match age.num_days() {
+    days if days >= 365 => (days / 365, "year"),
+    days if days >= 30 => (days / 30, "month"),
+    days if days >= 1 => (days, "day"),
+    _ if age.num_hours() >= 1 => (age.num_hours(), "hour"),
+    _ => (age.num_minutes().max(0), "minute"),
+  };
+
+  let plural = if amount == 1 { "" } else { "s" };
+  format!("{amount} {unit}{plural} ago")
+}
+
+/// Publish date followed by its age: `2024-06-01, 3 days ago`.
+pub fn format_publish_time(published_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+  format!(
+    "{}, {}",
+    published_at.format("%Y-%m-%d"),
+    format_age(published_at, now)
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_min_age() {
+    assert_eq!("7d".parse::<MinAge>().unwrap(), MinAge(TimeDelta::days(7)));
+    assert_eq!("3".parse::<MinAge>().unwrap(), MinAge(TimeDelta::days(3)));
+    assert_eq!(
+      "12h".parse::<MinAge>().unwrap(),
+      MinAge(TimeDelta::hours(12))
+    );
+    assert_eq!("2w".parse::<MinAge>().unwrap(), MinAge(TimeDelta::weeks(2)));
+    assert!("d".parse::<MinAge>().is_err());
+    assert!("7 days".parse::<MinAge>().is_err());
+  }
+
+  #[test]
+  fn test_published_before() {
+    let now: DateTime<Utc> = "2024-06-01T10:00:00Z".parse().unwrap();
+
+    assert_eq!(
+      "7d".parse::<MinAge>().unwrap().published_before(now),
+      now - TimeDelta::days(7)
+    );
+    assert_eq!(
+      "100000000d"
+        .parse::<MinAge>()
+        .unwrap()
+        .published_before(now),
+      DateTime::<Utc>::MIN_UTC
+    );
+  }
+
+  #[test]
+  fn test_format_age() {
+    let now = Utc::now();
+
+    assert_eq!(
+      format_age(now - TimeDelta::minutes(5), now),
+      "5 minutes ago"
+    );
+    assert_eq!(format_age(now - TimeDelta::hours(1), now), "1 hour ago");
+    assert_eq!(format_age(now - TimeDelta::days(3), now), "3 days ago");
+    assert_eq!(format_age(now - TimeDelta::days(400), now), "1 year ago");
+  }
+
+  #[test]
+  fn test_format_publish_time() {
+    let published_at: DateTime<Utc> = "2024-06-01T10:00:00Z".parse().unwrap();
+
+    assert_eq!(
+      format_publish_time(published_at, published_at + TimeDelta::days(45)),
+      "2024-06-01, 1 month ago"
+    );
+  }
+}
@@ -1,7 +1,7 @@
 use nodejs_semver::Version;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct DistTags {
   pub latest: String,
   pub next: Option<String>,
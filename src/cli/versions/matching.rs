@@ -1,43 +1,264 @@
 use anyhow::Result;
 use nodejs_semver::{Range, Version, VersionDiff};
 
-use crate::cli::flags::Flags;
+use super::{current_version_of, DistTags, Packument, UpdateRule, VersionTarget};
 
-use super::{normalize_version, DistTags, VersionTarget};
-
-pub fn match_dist_tag_with_target(dist_tags: DistTags, target: &VersionTarget) -> Option<String> {
-  match target {
+pub fn match_dist_tag_with_target(dist_tags: &DistTags, rule: &UpdateRule) -> Option<String> {
+  let version = match rule.target {
     VersionTarget::Pre => dist_tags.highest_prerelease_version(),
-    VersionTarget::Next => dist_tags.next,
-    VersionTarget::Canary => dist_tags.canary,
-    VersionTarget::Rc => dist_tags.rc,
-    VersionTarget::Beta => dist_tags.beta,
-    VersionTarget::Alpha => dist_tags.alpha,
-    _ => Some(dist_tags.latest),
-  }
+    VersionTarget::Next => dist_tags.next.clone(),
+    VersionTarget::Canary => dist_tags.canary.clone(),
+    VersionTarget::Rc => dist_tags.rc.clone(),
+    VersionTarget::Beta => dist_tags.beta.clone(),
+    VersionTarget::Alpha => dist_tags.alpha.clone(),
+    _ => Some(dist_tags.latest.clone()),
+  };
+
+  version.filter(|version| Version::parse(version).is_ok_and(|version| !rule.is_ignored(&version)))
 }
 
 pub fn is_version_satisfying(
   current_version: &str,
   latest_version: &str,
-  flags: &Flags,
+  rule: &UpdateRule,
 ) -> Result<bool> {
-  let current = Version::parse(normalize_version(current_version))?;
+  let current = current_version_of(current_version)?;
   let latest = Version::parse(latest_version)?;
 
   let diff = current.diff(&latest);
-  if diff.is_none() || latest <= current {
+  if diff.is_none() || latest <= current || rule.is_ignored(&latest) {
     return Ok(false);
   }
 
-  let matching_version = match flags.target {
+  let matching_version = match rule.target {
     VersionTarget::Latest => !current.is_prerelease(),
     VersionTarget::Semver => Range::parse(current_version)?.satisfies(&latest),
     VersionTarget::Major => diff == Some(VersionDiff::Major),
-    VersionTarget::Minor => diff == Some(VersionDiff::Minor),
+    VersionTarget::Minor => matches!(diff, Some(VersionDiff::Minor | VersionDiff::Patch)),
     VersionTarget::Patch => diff == Some(VersionDiff::Patch),
     _ => latest.is_prerelease(),
   };
 
   Ok(matching_version)
 }
+
+/// Find the highest version of the packument matching the target.
+///
+/// Range based targets (`semver`, `minor`, `patch`) look at every published version so that an
+/// in-range release is found even when `latest` is a new major, the others follow the dist-tags.
+/// With ignored or too recent versions, `latest` falls back to the highest stable version left.
+pub fn find_target_version(
+  current_version: &str,
+  packument: &Packument,
+  rule: &UpdateRule,
+) -> Result<Option<String>> {
+  if rule.security_fix.is_some() {
+    return find_security_fix(current_version, packument, rule);
+  }
+
+  let all_versions = || {
+    packument
+      .versions
+      .values()
+      .map(|manifest| manifest.version.clone())
+  };
+  let candidates: Vec<String> = match rule.target {
+    VersionTarget::Semver | VersionTarget::Minor | VersionTarget::Patch => all_versions().collect(),
+    VersionTarget::Latest if rule.ignore.is_some() || rule.published_before.is_some() => {
+      all_versions()
+        .filter(|version| Version::parse(version).is_ok_and(|version| !version.is_prerelease()))
+        .collect()
+    }
+    _ => match_dist_tag_with_target(&packument.dist_tags, rule)
+      .into_iter()
+      .collect(),
+  };
+
+  let mut best_version: Option<Version> = None;
+  for candidate in candidates {
+    let Ok(version) = Version::parse(&candidate) else {
+      continue;
+    };
+
+    if best_version.as_ref().is_some_and(|best| *best >= version)
+      || !rule.is_old_enough(packument, &candidate)
+    {
+      continue;
+    }
+
+    if is_version_satisfying(current_version, &candidate, rule)? {
+      best_version = Some(version);
+    }
+  }
+
+  Ok(best_version.map(|version| version.to_string()))
+}
+
+/// Lowest stable version above the current one that none of the advisories affects.
+fn find_security_fix(
+  current_version: &str,
+  packument: &Packument,
+  rule: &UpdateRule,
+) -> Result<Option<String>> {
+  let current = current_version_of(current_version)?;
+  if !rule.is_vulnerable(&current) {
+    return Ok(None);
+  }
+
+  let fix = packument
+    .versions
+    .values()
+    .filter(|manifest| rule.is_old_enough(packument, &manifest.version))
+    .filter_map(|manifest| Version::parse(&manifest.version).ok())
+    .filter(|version| {
+      *version > current
+        && !version.is_prerelease()
+        && !rule.is_ignored(version)
+        && !rule.is_vulnerable(version)
+    })
+    .min();
+
+  Ok(fix.map(|version| version.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn packument() -> Packument {
+    serde_json::from_value(serde_json::json!({
+      "dist-tags": { "latest": "2.0.0", "next": "3.0.0-rc.1" },
+      "versions": {
+        "1.0.0": { "version": "1.0.0" },
+        "1.0.1": { "version": "1.0.1" },
+        "1.1.0": { "version": "1.1.0" },
+        "1.2.0-beta.0": { "version": "1.2.0-beta.0" },
+        "2.0.0": { "version": "2.0.0" },
+        "3.0.0-rc.1": { "version": "3.0.0-rc.1" }
+      },
+      "time": {
+        "1.0.0": "2024-01-01T00:00:00.000Z",
+        "1.0.1": "2024-02-01T00:00:00.000Z",
+        "1.1.0": "2024-03-01T00:00:00.000Z",
+        "2.0.0": "2024-04-01T00:00:00.000Z"
+      }
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn test_find_target_version_from_dist_tags() {
+    let packument = packument();
+
+    let latest = find_target_version("^1.0.0", &packument, &VersionTarget::Latest.into()).unwrap();
+    let next = find_target_version("^1.0.0", &packument, &VersionTarget::Next.into()).unwrap();
+
+    assert_eq!(latest.as_deref(), Some("2.0.0"));
+    assert_eq!(next.as_deref(), Some("3.0.0-rc.1"));
+  }
+
+  #[test]
+  fn test_find_target_version_in_range() {
+    let packument = packument();
+
+    let semver = find_target_version("^1.0.0", &packument, &VersionTarget::Semver.into()).unwrap();
+    let minor = find_target_version("1.0.0", &packument, &VersionTarget::Minor.into()).unwrap();
+    let patch = find_target_version("~1.0.0", &packument, &VersionTarget::Patch.into()).unwrap();
+
+    assert_eq!(semver.as_deref(), Some("1.1.0"));
+    assert_eq!(minor.as_deref(), Some("1.1.0"));
+    assert_eq!(patch.as_deref(), Some("1.0.1"));
+  }
+
+  #[test]
+  fn test_find_target_version_up_to_date() {
+    let packument = packument();
+
+    let latest = find_target_version("2.0.0", &packument, &VersionTarget::Latest.into()).unwrap();
+    let patch = find_target_version("1.1.0", &packument, &VersionTarget::Patch.into()).unwrap();
+
+    assert_eq!(latest, None);
+    assert_eq!(patch, None);
+  }
+
+  #[test]
+  fn test_find_target_version_with_ignored_versions() {
+    let packument = packument();
+    let rule = |target, ignore| UpdateRule {
+      target,
+      ignore: Some(Range::parse(ignore).unwrap()),
+      ..Default::default()
+    };
+
+    let held =
+      find_target_version("1.0.0", &packument, &rule(VersionTarget::Latest, ">=2")).unwrap();
+    let waiting =
+      find_target_version("1.0.0", &packument, &rule(VersionTarget::Minor, "<1.1.0")).unwrap();
+    let pinned =
+      find_target_version("1.0.0", &packument, &rule(VersionTarget::Latest, "*")).unwrap();
+    let next =
+      find_target_version("1.0.0", &packument, &rule(VersionTarget::Next, ">=3.0.0-0")).unwrap();
+
+    assert_eq!(held.as_deref(), Some("1.1.0"));
+    assert_eq!(waiting.as_deref(), Some("1.1.0"));
+    assert_eq!(pinned, None);
+    assert_eq!(next, None);
+  }
+
+  #[test]
+  fn test_find_target_version_security_fix() {
+    let packument = packument();
+    let rule = |ranges: &[&str]| UpdateRule {
+      security_fix: Some(
+        ranges
+          .iter()
+          .map(|range| Range::parse(range).unwrap())
+          .collect(),
+      ),
+      ..Default::default()
+    };
+
+    let fix = find_target_version("^1.0.0", &packument, &rule(&["<1.0.1"])).unwrap();
+    let major_fix = find_target_version("1.0.0", &packument, &rule(&["<1.0.1", "1.x"])).unwrap();
+    let not_vulnerable = find_target_version("1.0.0", &packument, &rule(&[">=2"])).unwrap();
+    let no_advisory = find_target_version("1.0.0", &packument, &rule(&[])).unwrap();
+
+    assert_eq!(fix.as_deref(), Some("1.0.1"));
+    assert_eq!(major_fix.as_deref(), Some("2.0.0"));
+    assert_eq!(not_vulnerable, None);
+    assert_eq!(no_advisory, None);
+  }
+
+  #[test]
+  fn test_find_target_version_with_min_age() {
+    let packument = packument();
+    let rule = |target, published_before: &str| UpdateRule {
+      target,
+      published_before: Some(published_before.parse().unwrap()),
+      ..Default::default()
+    };
+
+    let latest = find_target_version(
+      "1.0.0",
+      &packument,
+      &rule(VersionTarget::Latest, "2024-03-15T00:00:00Z"),
+    )
+    .unwrap();
+    let patch = find_target_version(
+      "1.0.0",
+      &packument,
+      &rule(VersionTarget::Patch, "2024-01-15T00:00:00Z"),
+    )
+    .unwrap();
+    let unknown_time = find_target_version(
+      "1.0.0",
+      &packument,
+      &rule(VersionTarget::Next, "2025-01-01T00:00:00Z"),
+    )
+    .unwrap();
+
+    assert_eq!(latest.as_deref(), Some("1.1.0"));
+    assert_eq!(patch, None);
+    assert_eq!(unknown_time, None);
+  }
+}
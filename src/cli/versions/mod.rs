@@ -1,9 +1,19 @@
+pub mod age;
 pub mod dist_tags;
 pub mod matching;
+pub mod packument;
+pub mod semver_diff;
+pub mod spec;
+pub mod update_rule;
 pub mod utils;
 pub mod version_target;
 
+pub use age::*;
 pub use dist_tags::*;
 pub use matching::*;
+pub use packument::*;
+pub use semver_diff::*;
+pub use spec::*;
+pub use update_rule::*;
 pub use utils::*;
 pub use version_target::*;
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use nodejs_semver::Version;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::DistTags;
+
+/// Package document, abbreviated (`application/vnd.npm.install-v1+json`) unless publish times are needed.
+#[derive(Debug, Deserialize)]
+pub struct Packument {
+  #[serde(rename = "dist-tags")]
+  pub dist_tags: DistTags,
+  #[serde(default)]
+  pub versions: HashMap<String, PackumentVersion>,
+  /// Publish time of each version, only in the full document.
+  #[serde(default)]
+  pub time: HashMap<String, String>,
+  /// Source repository, only in the full document.
+  pub repository: Option<Repository>,
+}
+
+impl Packument {
+  pub fn published_at(&self, version: &str) -> Option<DateTime<Utc>> {
+    self.time.get(version)?.parse().ok()
+  }
+
+  pub fn repository_url(&self) -> Option<String> {
+    match self.repository.as_ref()? {
+      Repository::Url(url) | Repository::Object { url } => Some(url.clone()),
+    }
+  }
+
+  /// Peer dependencies declared by a version, empty when it has none or isn't known.
+  pub fn peer_dependencies_of(&self, version: &str) -> HashMap<String, String> {
+    self
+      .versions
+      .get(version)
+      .map(|version| version.peer_dependencies.clone())
+      .unwrap_or_default()
+  }
+
+  /// Deprecation message of a version, none when it isn't deprecated or isn't known.
+  pub fn deprecation_of(&self, version: &str) -> Option<String> {
+    self.versions.get(version)?.deprecated.clone()
+  }
+
+  pub fn tarball_of(&self, version: &str) -> Option<String> {
+    Some(self.versions.get(version)?.dist.as_ref()?.tarball.clone())
+  }
+
+  /// Build a packument out of bare versions, such as git tags, `latest` being the highest stable one.
+  pub fn from_versions(versions: impl IntoIterator<Item = Version>) -> Self {
+    let versions: Vec<Version> = versions.into_iter().collect();
+    let latest = versions
+      .iter()
+      .filter(|version| !version.is_prerelease())
+      .max()
+      .map(ToString::to_string)
+      .unwrap_or_default();
+
+    Self {
+      dist_tags: DistTags {
+        latest,
+        ..Default::default()
+      },
+      versions: versions
+        .into_iter()
+        .map(|version| {
+          let version = version.to_string();
+          (
+            version.clone(),
+            PackumentVersion {
+              version,
+              ..Default::default()
+            },
+          )
+        })
+        .collect(),
+      time: HashMap::new(),
+      repository: None,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PackumentVersion {
+  pub version: String,
+  pub dist: Option<Dist>,
+  #[serde(default)]
+  pub peer_dependencies: HashMap<String, String>,
+  #[serde(default, deserialize_with = "deprecation_message")]
+  pub deprecated: Option<String>,
+}
+
+/// Keep the message of a deprecated version, old packuments using `false` or an empty string for
+/// the others.
+fn deprecation_message<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let value: Option<serde_json::Value> = Deserialize::deserialize(deserializer)?;
+
+  Ok(match value {
+    Some(serde_json::Value::String(message)) if !message.trim().is_empty() => Some(message),
+    _ => None,
+  })
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct Dist {
+  pub tarball: String,
+}
+
+/// The `repository` field, either a shorthand or URL string or a `{ type, url, directory }` object.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Repository {
+  Url(String),
+  Object { url: String },
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_deprecation_of() {
+    let packument: Packument = serde_json::from_value(serde_json::json!({
+      "dist-tags": { "latest": "2.0.0" },
+      "versions": {
+        "1.0.0": { "version": "1.0.0", "deprecated": "Please use 2.x" },
+        "1.1.0": { "version": "1.1.0", "deprecated": false },
+        "1.2.0": { "version": "1.2.0", "deprecated": "" },
+        "2.0.0": { "version": "2.0.0" }
+      }
+    }))
+    .unwrap();
+
+    assert_eq!(
+      packument.deprecation_of("1.0.0").as_deref(),
+      Some("Please use 2.x")
+    );
+    assert_eq!(packument.deprecation_of("1.1.0"), None);
+    assert_eq!(packument.deprecation_of("1.2.0"), None);
+    assert_eq!(packument.deprecation_of("2.0.0"), None);
+    assert_eq!(packument.deprecation_of("3.0.0"), None);
+  }
+}
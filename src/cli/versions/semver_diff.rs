@@ -0,0 +1,50 @@
+use core::fmt;
+
+use nodejs_semver::{Version, VersionDiff};
+use serde::Serialize;
+
+use super::current_version_of;
+
+/// Kind of update between the current and the target version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemverDiff {
+  Major,
+  Minor,
+  Patch,
+  Prerelease,
+}
+
+impl SemverDiff {
+  pub fn between(current_version: &str, target_version: &str) -> Option<Self> {
+    let current = current_version_of(current_version).ok()?;
+    let target = Version::parse(target_version).ok()?;
+
+    current.diff(&target).map(Self::from)
+  }
+}
+
+impl From<VersionDiff> for SemverDiff {
+  fn from(diff: VersionDiff) -> Self {
+    match diff {
+      VersionDiff::Major => SemverDiff::Major,
+      VersionDiff::Minor => SemverDiff::Minor,
+      VersionDiff::Patch => SemverDiff::Patch,
+      VersionDiff::PreMajor
+      | VersionDiff::PreMinor
+      | VersionDiff::PrePatch
+      | VersionDiff::PreRelease => SemverDiff::Prerelease,
+    }
+  }
+}
+
+impl fmt::Display for SemverDiff {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SemverDiff::Major => write!(f, "major"),
+      SemverDiff::Minor => write!(f, "minor"),
+      SemverDiff::Patch => write!(f, "patch"),
+      SemverDiff::Prerelease => write!(f, "prerelease"),
+    }
+  }
+}
@@ -0,0 +1,248 @@
+use nodejs_semver::{Range, Version};
+
+use super::update_range;
+
+/// Classification of a `package.json` dependency specifier.
+#[derive(Debug, PartialEq)]
+pub enum DependencySpec<'a> {
+  /// A semver range or version served by the registry: `^1.2.0`, `1.x`, `*`.
+  Registry(&'a str),
+  /// Another registry package installed under a different name: `npm:other@^2`.
+  Alias { name: &'a str, range: &'a str },
+  /// A git repository, with the semver part of its reference when it has one: `#v1.2.0`, `#semver:^1.2`.
+  Git { url: String, range: Option<&'a str> },
+  /// A remote tarball: `https://example.com/pkg.tgz`.
+  Tarball,
+  /// A local directory or tarball: `file:../pkg`, `./pkg.tgz`.
+  File,
+  /// A symlinked local directory: `link:../pkg`, `portal:../pkg`.
+  Link,
+  /// A package of the same monorepo: `workspace:^`.
+  Workspace,
+  /// A dist-tag: `latest`, `next`.
+  Tag,
+}
+
+impl<'a> DependencySpec<'a> {
+  pub fn parse(spec: &'a str) -> Self {
+    let spec = spec.trim();
+
+    if let Some(alias) = spec.strip_prefix("npm:") {
+      // The name may be scoped, the range follows the last `@`
+      let (name, range) = match alias.rfind('@') {
+        Some(index) if index > 0 => (&alias[..index], &alias[index + 1..]),
+        _ => (alias, ""),
+      };
+      return DependencySpec::Alias { name, range };
+    }
+
+    if spec.starts_with("workspace:") {
+      return DependencySpec::Workspace;
+    }
+
+    if spec.starts_with("link:") || spec.starts_with("portal:") {
+      return DependencySpec::Link;
+    }
+
+    if spec.starts_with("file:") || spec.starts_with(['.', '/']) || spec.starts_with("~/") {
+      return DependencySpec::File;
+    }
+
+    if let Some(git) = parse_git(spec) {
+      return git;
+    }
+
+    if spec.starts_with("http://") || spec.starts_with("https://") {
+      return DependencySpec::Tarball;
+    }
+
+    if Range::parse(spec).is_ok() {
+      return DependencySpec::Registry(spec);
+    }
+
+    DependencySpec::Tag
+  }
+
+  /// Why the dependency can't be checked for updates, `None` when it can.
+  pub fn skip_reason(&self) -> Option<&'static str> {
+    match self {
+      DependencySpec::Registry(range) | DependencySpec::Alias { range, .. }
+        if matches!(*range, "" | "*" | "x" | "X") =>
+      {
+        Some("any version is allowed")
+      }
+      DependencySpec::Registry(_) | DependencySpec::Alias { .. } => None,
+      DependencySpec::Git { range: None, .. } => Some("git reference is not a semver tag"),
+      DependencySpec::Git { .. } => None,
+      DependencySpec::Tarball => Some("remote tarballs are not versioned"),
+      DependencySpec::File => Some("local file dependency"),
+      DependencySpec::Link => Some("linked local dependency"),
+      DependencySpec::Workspace => Some("workspace dependency"),
+      DependencySpec::Tag => Some("dist-tags always resolve to the tagged version"),
+    }
+  }
+}
+
+/// Build the specifier pointing to `version`, keeping the protocol, alias and range style of `spec`.
+pub fn update_spec(spec: &str, version: &str) -> String {
+  match DependencySpec::parse(spec) {
+    DependencySpec::Alias { name, range } => {
+      format!("npm:{name}@{}", update_range(range, version))
+    }
+    DependencySpec::Git { .. } => {
+      let Some((repository, reference)) = spec.trim().split_once('#') else {
+        return spec.to_owned();
+      };
+
+      let reference = match reference.strip_prefix("semver:") {
+        Some(range) => format!("semver:{}", update_range(range, version)),
+        None if reference.starts_with('v') => format!("v{version}"),
+        None => version.to_owned(),
+      };
+      format!("{repository}#{reference}")
+    }
+    _ => update_range(spec, version),
+  }
+}
+
+/// Recognize git specifiers and resolve the URL to list their tags from.
+fn parse_git(spec: &str) -> Option<DependencySpec<'_>> {
+  let (repository, reference) = match spec.split_once('#') {
+    Some((repository, reference)) => (repository, Some(reference)),
+    None => (spec, None),
+  };
+
+  let hosted = [
+    ("github:", "https://github.com/"),
+    ("gitlab:", "https://gitlab.com/"),
+    ("bitbucket:", "https://bitbucket.org/"),
+  ];
+
+  let url = if let Some((prefix, host)) = hosted
+    .iter()
+    .find(|(prefix, _)| repository.starts_with(prefix))
+  {
+    format!("{host}{}.git", &repository[prefix.len()..])
+  } else if let Some(url) = repository.strip_prefix("git+") {
+    url.to_owned()
+  } else if repository.starts_with("git://")
+    || repository.starts_with("git@")
+    || ((repository.starts_with("http://") || repository.starts_with("https://"))
+      && repository.ends_with(".git"))
+  {
+    repository.to_owned()
+  } else if is_github_shorthand(repository) {
+    format!("https://github.com/{repository}.git")
+  } else {
+    return None;
+  };
+
+  let range = reference.and_then(|reference| match reference.strip_prefix("semver:") {
+    Some(range) => Some(range),
+    None => Version::parse(reference).is_ok().then_some(reference),
+  });
+
+  Some(DependencySpec::Git { url, range })
+}
+
+/// `user/repo`, the default GitHub shorthand.
+fn is_github_shorthand(spec: &str) -> bool {
+  let mut parts = spec.split('/');
+  let is_segment = |part: Option<&str>| {
+    part.is_some_and(|part| {
+      !part.is_empty()
+        && part
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    })
+  };
+
+  !spec.starts_with('@')
+    && is_segment(parts.next())
+    && is_segment(parts.next())
+    && parts.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_registry_and_alias() {
+    assert_eq!(
+      DependencySpec::parse("^1.2.0"),
+      DependencySpec::Registry("^1.2.0")
+    );
+    assert_eq!(
+      DependencySpec::parse("1.x"),
+      DependencySpec::Registry("1.x")
+    );
+    assert_eq!(
+      DependencySpec::parse("npm:@scope/other@^2"),
+      DependencySpec::Alias {
+        name: "@scope/other",
+        range: "^2"
+      }
+    );
+    assert_eq!(DependencySpec::parse("latest"), DependencySpec::Tag);
+  }
+
+  #[test]
+  fn test_parse_git() {
+    assert_eq!(
+      DependencySpec::parse("github:foo/bar#v1.2.0"),
+      DependencySpec::Git {
+        url: "https://github.com/foo/bar.git".to_owned(),
+        range: Some("v1.2.0")
+      }
+    );
+    assert_eq!(
+      DependencySpec::parse("foo/bar#semver:^1.2"),
+      DependencySpec::Git {
+        url: "https://github.com/foo/bar.git".to_owned(),
+        range: Some("^1.2")
+      }
+    );
+    assert_eq!(
+      DependencySpec::parse("git+ssh://git@github.com/foo/bar.git#main"),
+      DependencySpec::Git {
+        url: "ssh://git@github.com/foo/bar.git".to_owned(),
+        range: None
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_skipped() {
+    let skipped = [
+      ("https://example.com/pkg-1.0.0.tgz", DependencySpec::Tarball),
+      ("file:../pkg", DependencySpec::File),
+      ("./pkg", DependencySpec::File),
+      ("link:../pkg", DependencySpec::Link),
+      ("workspace:^", DependencySpec::Workspace),
+    ];
+
+    for (spec, expected) in skipped {
+      let parsed = DependencySpec::parse(spec);
+      assert!(parsed.skip_reason().is_some());
+      assert_eq!(parsed, expected);
+    }
+
+    assert!(DependencySpec::parse("*").skip_reason().is_some());
+    assert!(DependencySpec::parse("~1.2.0").skip_reason().is_none());
+  }
+
+  #[test]
+  fn test_update_spec() {
+    assert_eq!(update_spec("^1.2.0", "1.3.0"), "^1.3.0");
+    assert_eq!(update_spec("npm:other@^2.0.0", "3.1.0"), "npm:other@^3.1.0");
+    assert_eq!(
+      update_spec("github:foo/bar#v1.2.0", "1.3.0"),
+      "github:foo/bar#v1.3.0"
+    );
+    assert_eq!(
+      update_spec("foo/bar#semver:~1.2.0", "1.2.4"),
+      "foo/bar#semver:~1.2.4"
+    );
+  }
+}
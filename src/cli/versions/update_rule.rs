@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use nodejs_semver::{Range, Version};
+
+use super::{Packument, VersionTarget};
+
+/// How a single package is updated.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateRule {
+  pub target: VersionTarget,
+  /// Versions never proposed: `*` pins the package, `>=19` holds it below 19, `<5.5.0` waits for 5.5.0.
+  pub ignore: Option<Range>,
+  /// Only versions published before this date are proposed, see `--min-age`.
+  pub published_before: Option<DateTime<Utc>>,
+  /// With `--security-only`, the ranges of the advisories affecting the current version: the
+  /// lowest version out of all of them is proposed, nothing when there are none.
+  pub security_fix: Option<Vec<Range>>,
+}
+
+impl UpdateRule {
+  pub fn is_ignored(&self, version: &Version) -> bool {
+    self
+      .ignore
+      .as_ref()
+      .is_some_and(|range| range.satisfies(version))
+  }
+
+  /// Whether `version` is affected by one of the advisories to fix.
+  pub fn is_vulnerable(&self, version: &Version) -> bool {
+    self
+      .security_fix
+      .iter()
+      .flatten()
+      .any(|range| range.satisfies(version))
+  }
+
+  /// Whether `version` is old enough, versions without a known publish time never are.
+  pub fn is_old_enough(&self, packument: &Packument, version: &str) -> bool {
+    self.published_before.is_none_or(|published_before| {
+      packument
+        .published_at(version)
+        .is_some_and(|published_at| published_at <= published_before)
+    })
+  }
+}
+
+impl From<VersionTarget> for UpdateRule {
+  fn from(target: VersionTarget) -> Self {
+    Self {
+      target,
+      ..Default::default()
+    }
+  }
+}
@@ -1,3 +1,111 @@
+use nodejs_semver::{Range, SemverError, Version};
+
 pub fn normalize_version(version: &str) -> &str {
   version.trim_start_matches(|c: char| !c.is_numeric())
 }
+
+/// Version a range currently resolves from: `^1.2.0` -> `1.2.0`, `^1` or `1.x` -> `1.0.0`.
+pub fn current_version_of(range: &str) -> Result<Version, SemverError> {
+  Version::parse(normalize_version(range))
+    .or_else(|error| Range::parse(range)?.min_version().ok_or(error))
+}
+
+/// Build the range pointing to `version` while keeping the operator (`^`, `~`, `>=`, exact...)
+/// and the precision (`1.x`, `^1.2`) of the current range.
+pub fn update_range(current_range: &str, version: &str) -> String {
+  let current_range = current_range.trim();
+  let current_version = normalize_version(current_range);
+  let prefix = &current_range[..current_range.len() - current_version.len()];
+
+  // Only the first comparator of compound ranges (`>=1.0.0 <2.0.0`) is kept
+  let current_version = current_version
+    .split(|c: char| c.is_whitespace() || c == '|')
+    .next()
+    .unwrap_or_default();
+
+  let core_parts: Vec<&str> = current_version
+    .split(['-', '+'])
+    .next()
+    .unwrap_or_default()
+    .split('.')
+    .collect();
+  let precision = core_parts
+    .iter()
+    .take_while(|part| part.parse::<u64>().is_ok())
+    .count();
+
+  let is_prerelease = version.contains(['-', '+']);
+  if precision == 0 || precision >= 3 || is_prerelease {
+    return format!("{prefix}{version}");
+  }
+
+  let mut range = version
+    .split('.')
+    .take(precision)
+    .collect::<Vec<_>>()
+    .join(".");
+  if let Some(wildcard) = core_parts.get(precision) {
+    range.push('.');
+    range.push_str(wildcard);
+  }
+
+  format!("{prefix}{range}")
+}
+
+/// Extend a range to also accept `version`: `^18.0.0` -> `^18.0.0 || ^19.0.0`.
+pub fn widen_range(current_range: &str, version: &str) -> String {
+  let current_range = current_range.trim();
+  let accepts_version = Range::parse(current_range)
+    .is_ok_and(|range| Version::parse(version).is_ok_and(|version| range.satisfies(&version)));
+  if accepts_version {
+    return current_range.to_owned();
+  }
+
+  // The added alternative follows the style of the last one
+  let last_range = current_range.rsplit("||").next().unwrap_or_default();
+  format!("{current_range} || {}", update_range(last_range, version))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_current_version_of() {
+    let current = |range| current_version_of(range).unwrap().to_string();
+
+    assert_eq!(current("^1.2.0"), "1.2.0");
+    assert_eq!(current("^1"), "1.0.0");
+    assert_eq!(current("1.x"), "1.0.0");
+    assert_eq!(current(">=1.2 <2"), "1.2.0");
+  }
+
+  #[test]
+  fn test_update_range_keeps_operator() {
+    assert_eq!(update_range("^1.2.0", "1.9.0"), "^1.9.0");
+    assert_eq!(update_range("~1.2.0", "1.2.5"), "~1.2.5");
+    assert_eq!(update_range(">=1.2.0", "2.0.0"), ">=2.0.0");
+    assert_eq!(update_range("1.2.0", "2.0.0"), "2.0.0");
+    assert_eq!(update_range("^1.0.0-beta.1", "1.0.0"), "^1.0.0");
+  }
+
+  #[test]
+  fn test_update_range_keeps_precision() {
+    assert_eq!(update_range("1.x", "2.3.4"), "2.x");
+    assert_eq!(update_range("~1.2.x", "1.4.0"), "~1.4.x");
+    assert_eq!(update_range("^1.2", "2.1.0"), "^2.1");
+    assert_eq!(update_range("^1", "3.0.0-rc.1"), "^3.0.0-rc.1");
+  }
+
+  #[test]
+  fn test_update_range_compound() {
+    assert_eq!(update_range(">=1.0.0 <2.0.0", "2.1.0"), ">=2.1.0");
+  }
+
+  #[test]
+  fn test_widen_range() {
+    assert_eq!(widen_range("^18.0.0", "19.0.0"), "^18.0.0 || ^19.0.0");
+    assert_eq!(widen_range("^17 || ^18", "19.1.0"), "^17 || ^18 || ^19");
+    assert_eq!(widen_range(">=18", "19.0.0"), ">=18");
+  }
+}
@@ -1,8 +1,10 @@
 use core::fmt;
 
 use clap::ValueEnum;
+use serde::Deserialize;
 
-#[derive(Debug, Clone, ValueEnum, Default)]
+#[derive(Debug, Clone, ValueEnum, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum VersionTarget {
   #[default]
   Latest,
@@ -0,0 +1,213 @@
+use anyhow::Result;
+use glob::{glob, Pattern};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::package_json::{PackageJson, PACKAGE_JSON_FILENAME};
+use crate::utils::fs::{read_json, read_yaml};
+
+pub static PNPM_WORKSPACE_FILENAME: &str = "pnpm-workspace.yaml";
+
+/// The `workspaces` field of `package.json`.
+///
+/// npm, yarn and bun accept an array of globs, yarn classic also accepts an object
+/// with a `packages` array (next to `nohoist`).
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum WorkspacesField {
+  Packages(Vec<String>),
+  Object {
+    #[serde(default)]
+    packages: Vec<String>,
+  },
+}
+
+impl WorkspacesField {
+  pub fn patterns(&self) -> &[String] {
+    match self {
+      Self::Packages(packages) | Self::Object { packages } => packages,
+    }
+  }
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct PnpmWorkspace {
+  #[serde(default)]
+  packages: Vec<String>,
+}
+
+/// A workspace member with its own `package.json`.
+#[derive(Debug, Default)]
+pub struct Workspace {
+  pub name: String,
+  pub file_path: PathBuf,
+  pub json: PackageJson,
+}
+
+impl Workspace {
+  /// Selects the member in `--workspace` and `--filter`: its name, or its directory when it has
+  /// none, as a `./` path so that pnpm and bun don't take it for a name.
+  pub fn selector(&self) -> String {
+    if self.json.name.is_some() {
+      self.name.clone()
+    } else {
+      format!("./{}", self.name)
+    }
+  }
+}
+
+/// Discover all workspace members declared by the root `package.json` or `pnpm-workspace.yaml`.
+pub fn discover_workspaces(root_file: &Path, root_json: &PackageJson) -> Result<Vec<Workspace>> {
+  let Some(root_dir) = root_file.parent() else {
+    return Ok(Vec::new());
+  };
+
+  let patterns = workspace_patterns(root_dir, root_json)?;
+  let (excludes, includes): (Vec<&String>, Vec<&String>) = patterns
+    .iter()
+    .partition(|pattern| pattern.starts_with('!'));
+
+  let excludes = excludes
+    .into_iter()
+    .filter_map(|pattern| Pattern::new(normalize_pattern(&pattern[1..])).ok())
+    .collect::<Vec<_>>();
+
+  let mut manifests = Vec::new();
+  for pattern in includes {
+    let full_pattern = format!(
+      "{}/{}/{}",
+      Pattern::escape(&root_dir.to_string_lossy()),
+      normalize_pattern(pattern),
+      PACKAGE_JSON_FILENAME
+    );
+
+    for file_path in glob(&full_pattern)?.flatten() {
+      let Some(relative_dir) = file_path
+        .parent()
+        .and_then(|dir| dir.strip_prefix(root_dir).ok())
+        .map(|dir| dir.to_string_lossy().replace('\\', "/"))
+      else {
+        continue;
+      };
+
+      let is_excluded = relative_dir.is_empty()
+        || relative_dir.split('/').any(|part| part == "node_modules")
+        || excludes
+          .iter()
+          .any(|exclude| exclude.matches(&relative_dir));
+
+      if !is_excluded && !manifests.iter().any(|(path, _)| path == &file_path) {
+        manifests.push((file_path, relative_dir));
+      }
+    }
+  }
+
+  manifests.sort();
+
+  manifests
+    .into_iter()
+    .map(|(file_path, relative_dir)| {
+      let json: PackageJson = read_json(&file_path)?;
+      Ok(Workspace {
+        name: json.name.clone().unwrap_or(relative_dir),
+        file_path,
+        json,
+      })
+    })
+    .collect()
+}
+
+/// `pnpm-workspace.yaml` takes precedence over the `workspaces` field, like pnpm itself does.
+fn workspace_patterns(root_dir: &Path, root_json: &PackageJson) -> Result<Vec<String>> {
+  let pnpm_workspace_path = root_dir.join(PNPM_WORKSPACE_FILENAME);
+  if pnpm_workspace_path.exists() {
+    let pnpm_workspace: PnpmWorkspace = read_yaml(pnpm_workspace_path)?;
+    return Ok(pnpm_workspace.packages);
+  }
+
+  Ok(
+    root_json
+      .workspaces
+      .as_ref()
+      .map(|workspaces| workspaces.patterns().to_vec())
+      .unwrap_or_default(),
+  )
+}
+
+fn normalize_pattern(pattern: &str) -> &str {
+  pattern.trim_start_matches("./").trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs;
+  use tempfile::tempdir;
+
+  fn write_manifest(dir: &Path, contents: &str) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(dir.join(PACKAGE_JSON_FILENAME), contents).unwrap();
+  }
+
+  #[test]
+  fn test_workspaces_field_forms() {
+    let array: PackageJson = serde_json::from_str(r#"{ "workspaces": ["packages/*"] }"#).unwrap();
+    let object: PackageJson =
+      serde_json::from_str(r#"{ "workspaces": { "packages": ["apps/*"], "nohoist": ["**/x"] } }"#)
+        .unwrap();
+
+    assert_eq!(array.workspaces.unwrap().patterns(), ["packages/*"]);
+    assert_eq!(object.workspaces.unwrap().patterns(), ["apps/*"]);
+  }
+
+  #[test]
+  fn test_discover_workspaces_from_package_json() {
+    let dir = tempdir().unwrap();
+    let root = r#"{ "workspaces": ["packages/*", "!packages/ignored"] }"#;
+    write_manifest(dir.path(), root);
+    write_manifest(&dir.path().join("packages/a"), r#"{ "name": "@scope/a" }"#);
+    write_manifest(&dir.path().join("packages/b"), "{}");
+    write_manifest(
+      &dir.path().join("packages/ignored"),
+      r#"{ "name": "ignored" }"#,
+    );
+
+    let root_json: PackageJson = serde_json::from_str(root).unwrap();
+    let workspaces =
+      discover_workspaces(&dir.path().join(PACKAGE_JSON_FILENAME), &root_json).unwrap();
+
+    let names: Vec<&str> = workspaces.iter().map(|ws| ws.name.as_str()).collect();
+    assert_eq!(names, ["@scope/a", "packages/b"]);
+    assert_eq!(workspaces[0].selector(), "@scope/a");
+    assert_eq!(workspaces[1].selector(), "./packages/b");
+    assert_eq!(
+      workspaces[0].file_path,
+      dir.path().join("packages/a").join(PACKAGE_JSON_FILENAME)
+    );
+  }
+
+  #[test]
+  fn test_discover_workspaces_from_pnpm_workspace() {
+    let dir = tempdir().unwrap();
+    write_manifest(dir.path(), "{}");
+    fs::write(
+      dir.path().join(PNPM_WORKSPACE_FILENAME),
+      "packages:\n  - 'apps/**'\n",
+    )
+    .unwrap();
+    write_manifest(&dir.path().join("apps/web"), r#"{ "name": "web" }"#);
+    write_manifest(
+      &dir.path().join("apps/web/node_modules/dep"),
+      r#"{ "name": "dep" }"#,
+    );
+
+    let workspaces = discover_workspaces(
+      &dir.path().join(PACKAGE_JSON_FILENAME),
+      &PackageJson::default(),
+    )
+    .unwrap();
+
+    assert_eq!(workspaces.len(), 1);
+    assert_eq!(workspaces[0].name, "web");
+  }
+}
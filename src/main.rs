@@ -3,18 +3,18 @@
 mod cli;
 mod utils;
 
+use std::process::ExitCode;
 use std::sync::Arc;
 
 use anyhow::Result;
-use clap::Parser;
 
 use cli::flags::Flags;
 use cli::package_json::PackageJsonManager;
 use cli::updater::UpdateChecker;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-  let flags = Arc::new(Flags::parse());
+async fn main() -> Result<ExitCode> {
+  let flags = Arc::new(Flags::load()?);
 
   let mut pkg_manager = PackageJsonManager::new(flags.clone());
   if !flags.global {
@@ -23,7 +23,5 @@ async fn main() -> Result<()> {
   }
 
   let update_checker = UpdateChecker::new(pkg_manager, flags.clone());
-  update_checker.run().await?;
-
-  Ok(())
+  update_checker.run().await
 }
@@ -36,6 +36,30 @@ where
   }
 }
 
+pub fn read_yaml<Yaml, FilePath>(file_path: FilePath) -> Result<Yaml>
+where
+  Yaml: serde::de::DeserializeOwned,
+  FilePath: AsRef<Path>,
+{
+  let mut file = File::open(file_path)?;
+  let mut contents = String::new();
+  file.read_to_string(&mut contents)?;
+
+  serde_yaml::from_str(&contents).map_err(|error| format_err!(error))
+}
+
+pub fn read_toml<Toml, FilePath>(file_path: FilePath) -> Result<Toml>
+where
+  Toml: serde::de::DeserializeOwned,
+  FilePath: AsRef<Path>,
+{
+  let mut file = File::open(file_path)?;
+  let mut contents = String::new();
+  file.read_to_string(&mut contents)?;
+
+  toml::from_str(&contents).map_err(|error| format_err!(error))
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,160 @@
+use anyhow::{format_err, Result};
+
+/// Replace the string value found at `path` in raw JSON `contents`, leaving the rest of the
+/// document (key order, indentation, trailing newline...) byte for byte untouched.
+pub fn replace_string_value(contents: &str, path: &[&str], value: &str) -> Result<String> {
+  let bytes = contents.as_bytes();
+  let not_found = || format_err!("Couldn't find \"{}\" in JSON document.", path.join("."));
+
+  let mut value_start = skip_whitespace(bytes, 0);
+  let mut value_end = value_end(bytes, value_start).ok_or_else(not_found)?;
+
+  for key in path {
+    (value_start, value_end) = find_member(bytes, value_start, key).ok_or_else(not_found)?;
+  }
+
+  if bytes.get(value_start) != Some(&b'"') {
+    return Err(format_err!(
+      "Expected a string at \"{}\" in JSON document.",
+      path.join(".")
+    ));
+  }
+
+  Ok(format!(
+    "{}{}{}",
+    &contents[..value_start],
+    serde_json::to_string(value)?,
+    &contents[value_end..]
+  ))
+}
+
+/// Find the member named `key` of the object starting at `object_start`, returning its value span.
+fn find_member(bytes: &[u8], object_start: usize, key: &str) -> Option<(usize, usize)> {
+  if bytes.get(object_start) != Some(&b'{') {
+    return None;
+  }
+
+  let mut pos = object_start + 1;
+  loop {
+    pos = skip_whitespace(bytes, pos);
+    if bytes.get(pos) != Some(&b'"') {
+      return None;
+    }
+
+    let key_end = string_end(bytes, pos)?;
+    let member_key: String = serde_json::from_slice(&bytes[pos..key_end]).ok()?;
+
+    pos = skip_whitespace(bytes, key_end);
+    if bytes.get(pos) != Some(&b':') {
+      return None;
+    }
+
+    let value_start = skip_whitespace(bytes, pos + 1);
+    let value_end = value_end(bytes, value_start)?;
+    if member_key == key {
+      return Some((value_start, value_end));
+    }
+
+    pos = skip_whitespace(bytes, value_end);
+    if bytes.get(pos) != Some(&b',') {
+      return None;
+    }
+    pos += 1;
+  }
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+  while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+    pos += 1;
+  }
+  pos
+}
+
+/// Index right after the closing quote of the string starting at `start`.
+fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+  let mut pos = start + 1;
+  loop {
+    match bytes.get(pos)? {
+      b'\\' => pos += 2,
+      b'"' => return Some(pos + 1),
+      _ => pos += 1,
+    }
+  }
+}
+
+/// Index right after the end of the value starting at `start`.
+fn value_end(bytes: &[u8], start: usize) -> Option<usize> {
+  match bytes.get(start)? {
+    b'"' => string_end(bytes, start),
+    b'{' | b'[' => {
+      let mut depth = 0;
+      let mut pos = start;
+      loop {
+        match bytes.get(pos)? {
+          b'"' => {
+            pos = string_end(bytes, pos)?;
+            continue;
+          }
+          b'{' | b'[' => depth += 1,
+          b'}' | b']' => {
+            depth -= 1;
+            if depth == 0 {
+              return Some(pos + 1);
+            }
+          }
+          _ => {}
+        }
+        pos += 1;
+      }
+    }
+    _ => {
+      let mut pos = start;
+      while bytes
+        .get(pos)
+        .is_some_and(|byte| !matches!(byte, b',' | b'}' | b']') && !byte.is_ascii_whitespace())
+      {
+        pos += 1;
+      }
+      Some(pos)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const PACKAGE_JSON: &str = r#"{
+    "name": "app",
+    "scripts": { "dependencies": "not this one" },
+    "dependencies": {
+        "react": "^18.2.0",
+        "react-dom":"~18.2.0"
+    },
+    "pnpm": { "overrides": { "lodash": "4.17.0" } }
+}
+"#;
+
+  #[test]
+  fn test_replace_string_value() {
+    let result = replace_string_value(PACKAGE_JSON, &["dependencies", "react-dom"], "~19.0.0");
+
+    assert_eq!(
+      result.unwrap(),
+      PACKAGE_JSON.replace(r#""react-dom":"~18.2.0""#, r#""react-dom":"~19.0.0""#)
+    );
+  }
+
+  #[test]
+  fn test_replace_nested_string_value() {
+    let result = replace_string_value(PACKAGE_JSON, &["pnpm", "overrides", "lodash"], "4.17.21");
+
+    assert_eq!(result.unwrap(), PACKAGE_JSON.replace("4.17.0", "4.17.21"));
+  }
+
+  #[test]
+  fn test_replace_missing_value() {
+    assert!(replace_string_value(PACKAGE_JSON, &["devDependencies", "react"], "1.0.0").is_err());
+    assert!(replace_string_value(PACKAGE_JSON, &["scripts"], "1.0.0").is_err());
+  }
+}